@@ -0,0 +1,158 @@
+"""AEAD modes, over any cipher with `bs` and `enc`."""
+from mac import cmac, dbl, ntz
+from rc5 import xor
+
+
+def ctr_full(c, iv, m):
+    """Big-endian counter over the whole block, as EAX and SIV use it"""
+    bs = c.bs
+    counter, out = int.from_bytes(iv, "big"), b""
+    for i in range(0, len(m), bs):
+        ks = c.enc((counter % (1 << (8 * bs))).to_bytes(bs, "big"))
+        out += xor(m[i : i + bs], ks)
+        counter += 1
+    return out
+
+
+def eax_enc(c, nonce, header, m, tag_len=None):
+    tag_len = tag_len or c.bs
+
+    def omac(t, data):
+        return cmac(c, bytes(c.bs - 1) + bytes([t]) + data)
+
+    n, h = omac(0, nonce), omac(1, header)
+    ct = ctr_full(c, n, m)
+    return ct + xor(xor(n, h), omac(2, ct))[:tag_len]
+
+
+def _ghash(h, data):
+    def gmul(x, y):
+        z = 0
+        for i in range(127, -1, -1):
+            if (x >> i) & 1:
+                z ^= y
+            y = (y >> 1) ^ (0xE1 << 120) if y & 1 else y >> 1
+        return z
+
+    y = 0
+    for i in range(0, len(data), 16):
+        y = gmul(y ^ int.from_bytes(data[i : i + 16], "big"), h)
+    return y
+
+
+def gcm_enc(c, iv, a, p, tag_len=16):
+    def pad16(b):
+        return b + bytes(-len(b) % 16)
+
+    def inc32(b):
+        return b[:12] + ((int.from_bytes(b[12:], "big") + 1) % 2**32).to_bytes(4, "big")
+
+    h = int.from_bytes(c.enc(bytes(16)), "big")
+    if len(iv) == 12:
+        j0 = iv + b"\0\0\0\1"
+    else:
+        lens = bytes(8) + (8 * len(iv)).to_bytes(8, "big")
+        j0 = _ghash(h, pad16(iv) + lens).to_bytes(16, "big")
+    counter, out = j0, b""
+    for i in range(0, len(p), 16):
+        counter = inc32(counter)
+        out += xor(p[i : i + 16], c.enc(counter))
+    lens = (8 * len(a)).to_bytes(8, "big") + (8 * len(out)).to_bytes(8, "big")
+    s = _ghash(h, pad16(a) + pad16(out) + lens)
+    return out + xor(c.enc(j0), s.to_bytes(16, "big"))[:tag_len]
+
+
+def ccm_enc(c, nonce, a, p, tag_len, length_len):
+    bs, L = c.bs, length_len
+    assert len(nonce) == bs - 1 - L
+    flags = (0x40 if a else 0) | (((tag_len - 2) // 2) << 3) | (L - 1)
+    b = bytes([flags]) + nonce + len(p).to_bytes(L, "big")
+    if a:
+        if len(a) < (1 << 16) - (1 << 8):
+            encoded = len(a).to_bytes(2, "big")
+        elif len(a) < (1 << 32):
+            encoded = b"\xff\xfe" + len(a).to_bytes(4, "big")
+        else:
+            encoded = b"\xff\xff" + len(a).to_bytes(8, "big")
+        encoded += a
+        b += encoded + bytes(-len(encoded) % bs)
+    b += p + bytes(-len(p) % bs)
+    x = bytes(bs)
+    for i in range(0, len(b), bs):
+        x = c.enc(xor(x, b[i : i + bs]))
+
+    def ctr_block(i):
+        return bytes([L - 1]) + nonce + i.to_bytes(L, "big")
+
+    out = b""
+    for j, i in enumerate(range(0, len(p), bs)):
+        out += xor(p[i : i + bs], c.enc(ctr_block(j + 1)))
+    return out + xor(x[:tag_len], c.enc(ctr_block(0)))
+
+
+def s2v(c, components):
+    bs = c.bs
+    if not components:
+        return cmac(c, bytes(bs - 1) + b"\x01")
+    d = cmac(c, bytes(bs))
+    for s in components[:-1]:
+        d = xor(dbl(d), cmac(c, s))
+    last = components[-1]
+    if len(last) >= bs:
+        t = last[:-bs] + xor(last[-bs:], d)
+    else:
+        t = xor(dbl(d), last + b"\x80" + bytes(bs - len(last) - 1))
+    return cmac(c, t)
+
+
+def siv_enc(mac_cipher, ctr_cipher, ads, p):
+    v = s2v(mac_cipher, ads + [p])
+    q = bytearray(v)
+    if len(q) >= 8:
+        q[-4] &= 0x7F
+        q[-8] &= 0x7F
+    return v + ctr_full(ctr_cipher, bytes(q), p)
+
+
+def _ocb_setup(c):
+    l_star = c.enc(bytes(16))
+    l_dollar = dbl(l_star)
+    L = [dbl(l_dollar)]
+    for _ in range(64):
+        L.append(dbl(L[-1]))
+    return l_star, l_dollar, L
+
+
+def _ocb_hash(c, a):
+    l_star, _, L = _ocb_setup(c)
+    s, offset, m = bytes(16), bytes(16), len(a) // 16
+    for i in range(1, m + 1):
+        offset = xor(offset, L[ntz(i)])
+        s = xor(s, c.enc(xor(a[16 * (i - 1) : 16 * i], offset)))
+    if len(a) % 16:
+        offset = xor(offset, l_star)
+        s = xor(s, c.enc(xor(a[16 * m :] + b"\x80" + bytes(15 - len(a) % 16), offset)))
+    return s
+
+
+def ocb_enc(c, n, a, p, tag_len=16):
+    l_star, l_dollar, L = _ocb_setup(c)
+    nonce = ((tag_len * 8 % 128) << 121 | 1 << (8 * len(n)) | int.from_bytes(n, "big"))
+    nonce = nonce.to_bytes(16, "big")
+    bottom = nonce[15] & 0x3F
+    ktop = c.enc(nonce[:15] + bytes([nonce[15] & 0xC0]))
+    stretch = int.from_bytes(ktop + xor(ktop[:8], ktop[1:9]), "big")
+    offset = ((stretch >> (64 - bottom)) & ((1 << 128) - 1)).to_bytes(16, "big")
+    checksum, out, m = bytes(16), b"", len(p) // 16
+    for i in range(1, m + 1):
+        offset = xor(offset, L[ntz(i)])
+        block = p[16 * (i - 1) : 16 * i]
+        out += xor(offset, c.enc(xor(block, offset)))
+        checksum = xor(checksum, block)
+    if len(p) % 16:
+        tail = p[16 * m :]
+        offset = xor(offset, l_star)
+        out += xor(tail, c.enc(offset))
+        checksum = xor(checksum, tail + b"\x80" + bytes(15 - len(tail)))
+    tag = xor(c.enc(xor(xor(checksum, offset), l_dollar)), _ocb_hash(c, a))
+    return out + tag[:tag_len]
@@ -0,0 +1,129 @@
+"""Runs the reference constructions over AES (and TDEA for CMAC) and checks them
+against the `cryptography` package and the published vectors.
+
+    pip install cryptography
+    python3 check_aes.py
+"""
+import os
+
+from cryptography.hazmat.primitives import cmac as crypto_cmac
+from cryptography.hazmat.primitives.ciphers import Cipher, algorithms, modes
+from cryptography.hazmat.primitives.ciphers.aead import AESCCM, AESGCM, AESOCB3, AESSIV
+
+try:
+    from cryptography.hazmat.decrepit.ciphers.algorithms import TripleDES
+except ImportError:
+    TripleDES = algorithms.TripleDES
+
+from aead import ccm_enc, eax_enc, gcm_enc, ocb_enc, siv_enc
+from drbg import CtrDrbg
+from mac import cmac, pmac, poly1305
+
+
+class Ecb:
+    def __init__(self, key, algorithm=algorithms.AES):
+        self.key, self.algorithm = key, algorithm
+        self.bs = algorithm.block_size // 8
+
+    def enc(self, block):
+        e = Cipher(self.algorithm(self.key), modes.ECB()).encryptor()
+        return e.update(block) + e.finalize()
+
+
+def h(s):
+    return bytes.fromhex(s)
+
+
+def check_cmac():
+    for algorithm, key_len, lens in [
+        (algorithms.AES, 16, (0, 1, 15, 16, 17, 33)),
+        (TripleDES, 24, (0, 1, 7, 8, 9, 24)),
+    ]:
+        for n in lens:
+            key, m = os.urandom(key_len), os.urandom(n)
+            expected = crypto_cmac.CMAC(algorithm(key))
+            expected.update(m)
+            assert cmac(Ecb(key, algorithm), m) == expected.finalize(), n
+
+
+def check_eax():
+    # The first two vectors of the EAX paper
+    c = Ecb(h("233952DEE4D5ED5F9B9C6D6FF80FF478"))
+    ct = eax_enc(c, h("62EC67F9C3A4A407FCB2A8C49031A8B3"), h("6BFB914FD07EAE6B"), b"")
+    assert ct == h("E037830E8389F27B025A2D6527E79D01")
+    c = Ecb(h("91945D3F4DCBEE0BF45EF52255F095A4"))
+    ct = eax_enc(c, h("BECAF043B0A23D843194BA972C66DEBD"), h("FA3BFD4806EB53FA"), h("F7FB"))
+    assert ct == h("19DD5C4C9331049D0BDAB0277408F67967E5")
+
+
+def check_gcm():
+    for iv_len in (12, 8, 16, 60):
+        for pt_len in (0, 5, 16, 33):
+            key, iv, ad, pt = (os.urandom(n) for n in (16, iv_len, 7, pt_len))
+            assert gcm_enc(Ecb(key), iv, ad, pt) == AESGCM(key).encrypt(iv, pt, ad)
+
+
+def check_ccm():
+    for length_len in range(2, 9):
+        for tag_len in (4, 8, 16):
+            for ad_len in (0, 3, 14, 300, 70000):
+                for pt_len in (0, 5, 16, 33):
+                    key, nonce = os.urandom(16), os.urandom(15 - length_len)
+                    ad, pt = os.urandom(ad_len), os.urandom(pt_len)
+                    expected = AESCCM(key, tag_len).encrypt(nonce, pt, ad or None)
+                    assert ccm_enc(Ecb(key), nonce, ad, pt, tag_len, length_len) == expected
+
+
+def check_siv():
+    for count in range(4):
+        for pt_len in (1, 5, 16, 33):
+            key, pt = os.urandom(32), os.urandom(pt_len)
+            ads = [os.urandom(7 * i) for i in range(count)]
+            expected = AESSIV(key).encrypt(pt, ads or None)
+            assert siv_enc(Ecb(key[:16]), Ecb(key[16:]), ads, pt) == expected
+
+
+def check_ocb():
+    for nonce_len in (12, 15):
+        for ad_len in (0, 5, 16, 40):
+            for pt_len in (0, 5, 16, 33, 100):
+                key, nonce = os.urandom(16), os.urandom(nonce_len)
+                ad, pt = os.urandom(ad_len), os.urandom(pt_len)
+                expected = AESOCB3(key).encrypt(nonce, pt, ad or None)
+                assert ocb_enc(Ecb(key), nonce, ad, pt) == expected
+
+
+def check_pmac():
+    # PMAC-AES-128 vectors of the paper, key and message are counting bytes
+    c = Ecb(bytes(range(16)))
+    for n, tag in [
+        (0, "4399572cd6ea5341b8d35876a7098af7"),
+        (3, "256ba5193c1b991b4df0c51f388a9e27"),
+        (16, "ebbd822fa458daf6dfdad7c27da76338"),
+        (20, "0412ca150bbf79058d8c75a58c993f55"),
+    ]:
+        assert pmac(c, bytes(range(n))) == h(tag), n
+
+
+def check_poly1305():
+    # The first Poly1305-AES vector of the paper
+    c = Ecb(h("ec074c835580741701425b623235add6"))
+    tag = poly1305(c, h("851fc40c3467ac0be05cc20404f3f700"), h("fb447350c4e868c52ac3275cf9d4327e"), h("f3f6"))
+    assert tag == h("f4c633c3044fc145f84f335cb81953de")
+
+
+def check_drbg():
+    # CTR_DRBG AES-128 with derivation function, no prediction resistance, count 0 of NIST CAVP
+    drbg = CtrDrbg(Ecb, 16, 16, h("890eb067acf7382eff80b0c73bc872c6"), h("aad471ef3ef1d203"), b"")
+    drbg.generate(64)
+    expected = (
+        "a5514ed7095f64f3d0d3a5760394ab42062f373a25072a6ea6bcfd8489e94af6"
+        "cf18659fea22ed1ca0a9e33f718b115ee536b12809c31b72b08ddd8be1910fa3"
+    )
+    assert drbg.generate(64) == h(expected)
+
+
+if __name__ == "__main__":
+    for check in [check_cmac, check_eax, check_gcm, check_ccm, check_siv, check_ocb, check_pmac, check_poly1305, check_drbg]:
+        check()
+        print(check.__name__, "ok")
@@ -0,0 +1,69 @@
+"""CTR_DRBG with derivation function, NIST SP 800-90A section 10.2."""
+from rc5 import xor
+
+
+class CtrDrbg:
+    def __init__(self, make_cipher, key_len, block_len, entropy, nonce, personalization):
+        self.make_cipher = make_cipher
+        self.key_len, self.block_len = key_len, block_len
+        self.seed_len = key_len + block_len
+        self.key, self.v = bytes(key_len), bytes(block_len)
+        self.update(self.df(entropy + nonce + personalization, self.seed_len))
+        self.reseed_counter = 1
+
+    def enc(self, key, block):
+        return self.make_cipher(key).enc(block)
+
+    def bcc(self, key, data):
+        chain = bytes(self.block_len)
+        for i in range(0, len(data), self.block_len):
+            chain = self.enc(key, xor(chain, data[i : i + self.block_len]))
+        return chain
+
+    def df(self, data, n):
+        s = len(data).to_bytes(4, "big") + n.to_bytes(4, "big") + data + b"\x80"
+        while len(s) % self.block_len:
+            s += b"\0"
+        key, temp, i = bytes(range(32))[: self.key_len], b"", 0
+        while len(temp) < self.seed_len:
+            if self.block_len < 4:
+                iv = i.to_bytes(4, "big")[-self.block_len :]
+            else:
+                iv = i.to_bytes(4, "big") + bytes(self.block_len - 4)
+            temp += self.bcc(key, iv + s)
+            i += 1
+        key, x, temp = temp[: self.key_len], temp[self.key_len : self.seed_len], b""
+        while len(temp) < n:
+            x = self.enc(key, x)
+            temp += x
+        return temp[:n]
+
+    def increment(self):
+        v = (int.from_bytes(self.v, "big") + 1) % (1 << (8 * self.block_len))
+        self.v = v.to_bytes(self.block_len, "big")
+
+    def update(self, provided):
+        temp = b""
+        while len(temp) < self.seed_len:
+            self.increment()
+            temp += self.enc(self.key, self.v)
+        temp = xor(temp[: self.seed_len], provided)
+        self.key, self.v = temp[: self.key_len], temp[self.key_len :]
+
+    def reseed(self, entropy, additional=b""):
+        self.update(self.df(entropy + additional, self.seed_len))
+        self.reseed_counter = 1
+
+    def generate(self, n, additional=b""):
+        if additional:
+            additional = self.df(additional, self.seed_len)
+            self.update(additional)
+        else:
+            additional = bytes(self.seed_len)
+        temp = b""
+        while len(temp) < n:
+            self.increment()
+            temp += self.enc(self.key, self.v)
+        self.update(additional)
+        self.reseed_counter += 1
+        return temp[:n]
@@ -0,0 +1,40 @@
+"""Single and double block length hashes built on RC5-w/r."""
+from rc5 import RC5, xor
+
+
+def md_pad(m, bs):
+    p = m + b"\x80"
+    while (len(p) + 8) % bs:
+        p += b"\0"
+    return p + (8 * len(m) % (1 << 64)).to_bytes(8, "big")
+
+
+def davies_meyer(m, w, r):
+    bs = w // 4
+    h, p = bytes(bs), md_pad(m, bs)
+    for i in range(0, len(p), bs):
+        h = xor(RC5(p[i : i + bs], w, r).enc(h), h)
+    return h
+
+
+def matyas_meyer_oseas(m, w, r):
+    bs = w // 4
+    h, p = bytes(bs), md_pad(m, bs)
+    for i in range(0, len(p), bs):
+        block = p[i : i + bs]
+        h = xor(RC5(h, w, r).enc(block), block)
+    return h
+
+
+def mdc2(m, w, r):
+    bs = w // 4
+    half = bs // 2
+    g, h, p = b"\x52" * bs, b"\x25" * bs, md_pad(m, bs)
+    for i in range(0, len(p), bs):
+        block = p[i : i + bs]
+        kg = bytes([(g[0] & 0x9F) | 0x40]) + g[1:]
+        kh = bytes([(h[0] & 0x9F) | 0x20]) + h[1:]
+        v1 = xor(RC5(kg, w, r).enc(block), block)
+        v2 = xor(RC5(kh, w, r).enc(block), block)
+        g, h = v1[:half] + v2[half:], v2[:half] + v1[half:]
+    return g + h
@@ -0,0 +1,93 @@
+"""MACs, over any cipher with `bs` and `enc`/`dec`."""
+from rc5 import xor
+
+# Low bits of the reduction polynomial per block size in bytes
+POLY = {2: 0x2B, 4: 0x8D, 8: 0x1B, 16: 0x87, 32: 0x425}
+
+
+def dbl(b):
+    n = len(b)
+    x = int.from_bytes(b, "big") << 1
+    if x >> (8 * n):
+        x ^= (1 << (8 * n)) | POLY[n]
+    return x.to_bytes(n, "big")
+
+
+def half(b):
+    n = len(b)
+    x = int.from_bytes(b, "big")
+    x = ((x ^ POLY[n]) >> 1) | (1 << (8 * n - 1)) if x & 1 else x >> 1
+    return x.to_bytes(n, "big")
+
+
+def ntz(i):
+    return (i & -i).bit_length() - 1
+
+
+def cmac(c, m):
+    bs = c.bs
+    k1 = dbl(c.enc(bytes(bs)))
+    k2 = dbl(k1)
+    if m and len(m) % bs == 0:
+        last = xor(m[-bs:], k1)
+        m = m[:-bs]
+    else:
+        r = len(m) % bs
+        last = xor(m[len(m) - r :] + b"\x80" + bytes(bs - r - 1), k2)
+        m = m[: len(m) - r]
+    x = bytes(bs)
+    for i in range(0, len(m), bs):
+        x = c.enc(xor(x, m[i : i + bs]))
+    return c.enc(xor(x, last))
+
+
+def iso9797_pad(m, bs, method):
+    if method == 1:
+        return m + bytes(-len(m) % bs) if m else bytes(bs)
+    if method == 2:
+        m += b"\x80"
+        return m + bytes(-len(m) % bs)
+    return (8 * len(m)).to_bytes(bs, "big") + m + bytes(-len(m) % bs)
+
+
+def cbc_raw(c, data):
+    x = bytes(c.bs)
+    for i in range(0, len(data), c.bs):
+        x = c.enc(xor(x, data[i : i + c.bs]))
+    return x
+
+
+def cbc_mac(c, m):
+    return cbc_raw(c, iso9797_pad(m, c.bs, 3))
+
+
+def retail_mac(c1, c2, m, method):
+    return c1.enc(c2.dec(cbc_raw(c1, iso9797_pad(m, c1.bs, method))))
+
+
+def pmac(c, m):
+    bs = c.bs
+    L = [c.enc(bytes(bs))]
+    for _ in range(64):
+        L.append(dbl(L[-1]))
+    blocks = max(1, -(-len(m) // bs))
+    offset, s = bytes(bs), bytes(bs)
+    for i in range(1, blocks):
+        offset = xor(offset, L[ntz(i)])
+        s = xor(s, c.enc(xor(m[bs * (i - 1) : bs * i], offset)))
+    last = m[bs * (blocks - 1) :]
+    if len(last) == bs:
+        s = xor(s, xor(last, half(L[0])))
+    else:
+        s = xor(s, last + b"\x80" + bytes(bs - len(last) - 1))
+    return c.enc(s)
+
+
+def poly1305(c, r, nonce, m):
+    p = (1 << 130) - 5
+    r = int.from_bytes(r, "little") & 0x0FFFFFFC0FFFFFFC0FFFFFFC0FFFFFFF
+    h = 0
+    for i in range(0, len(m), 16):
+        h = ((h + int.from_bytes(m[i : i + 16] + b"\x01", "little")) * r) % p
+    s = int.from_bytes(c.enc(nonce), "little")
+    return ((h + s) % (1 << 128)).to_bytes(16, "little")
@@ -0,0 +1,79 @@
+"""Confidentiality modes, over any cipher with `bs` and `enc`/`dec`."""
+from rc5 import xor
+
+
+def pkcs7(m, bs):
+    n = bs - len(m) % bs
+    return m + bytes([n]) * n
+
+
+def cbc_enc(c, iv, m):
+    out, prev = b"", iv
+    for i in range(0, len(m), c.bs):
+        prev = c.enc(xor(m[i : i + c.bs], prev))
+        out += prev
+    return out
+
+
+def pcbc_enc(c, iv, m):
+    out, chain = b"", iv
+    for i in range(0, len(m), c.bs):
+        p = m[i : i + c.bs]
+        ct = c.enc(xor(p, chain))
+        out += ct
+        chain = xor(p, ct)
+    return out
+
+
+def ige_enc(c, x0, y0, m):
+    out, px, py = b"", x0, y0
+    for i in range(0, len(m), c.bs):
+        x = m[i : i + c.bs]
+        y = xor(c.enc(xor(x, py)), px)
+        out += y
+        px, py = x, y
+    return out
+
+
+def cts_enc(c, iv, m, variant):
+    """`variant` is 1, 2 or 3 as in NIST SP 800-38A Addendum"""
+    bs = c.bs
+    assert len(m) >= bs
+    if len(m) == bs:
+        return cbc_enc(c, iv, m)
+    d = (len(m) - 1) % bs + 1
+    ct = cbc_enc(c, iv, m + bytes(bs - d))
+    head, cn1, cn = ct[: -2 * bs], ct[-2 * bs : -bs], ct[-bs:]
+    if variant == 1 or (variant == 2 and d == bs):
+        return head + cn1[:d] + cn
+    return head + cn + cn1[:d]
+
+
+def cfb_enc(c, iv, m, s):
+    """`s` is the segment size in bytes"""
+    reg, out = iv, b""
+    for i in range(0, len(m), s):
+        seg = m[i : i + s]
+        ct = xor(seg, c.enc(reg)[: len(seg)])
+        out += ct
+        reg = (reg + ct)[len(ct) :] if len(ct) == s else reg
+    return out
+
+
+def ofb(c, iv, m):
+    reg, out = iv, b""
+    for i in range(0, len(m), c.bs):
+        reg = c.enc(reg)
+        out += xor(m[i : i + c.bs], reg)
+    return out
+
+
+def ctr(c, nonce, m, offset=0):
+    """Big-endian counter in the block bytes after `nonce`"""
+    bs = c.bs
+    width = bs - len(nonce)
+    out = bytearray()
+    for pos, x in enumerate(m, offset):
+        ks = c.enc(nonce + (pos // bs).to_bytes(width, "big"))
+        out.append(x ^ ks[pos % bs])
+    return bytes(out)
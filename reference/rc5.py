@@ -0,0 +1,68 @@
+"""Plain RC5-w/r/b, a direct port of the RFC 2040 reference code."""
+import math
+
+P_Q = {
+    8: (0xB7, 0x9F),
+    16: (0xB7E1, 0x9E37),
+    32: (0xB7E15163, 0x9E3779B9),
+    64: (0xB7E151628AED2A6B, 0x9E3779B97F4A7C15),
+    128: (0xB7E151628AED2A6ABF7158809CF4F3C7, 0x9E3779B97F4A7C15F39CC0605CEDC835),
+}
+
+
+def rotl(x, n, w):
+    n %= w
+    return ((x << n) | (x >> (w - n))) & ((1 << w) - 1) if n else x
+
+
+def rotr(x, n, w):
+    n %= w
+    return ((x >> n) | (x << (w - n))) & ((1 << w) - 1) if n else x
+
+
+def xor(a, b):
+    return bytes(x ^ y for x, y in zip(a, b))
+
+
+class RC5:
+    def __init__(self, key, w=32, r=12):
+        self.w, self.r, self.u = w, r, w // 8
+        self.bs = 2 * self.u
+        mask = (1 << w) - 1
+        c = max(1, math.ceil(len(key) / self.u))
+        L = [0] * c
+        for i in range(len(key) - 1, -1, -1):
+            L[i // self.u] = (rotl(L[i // self.u], 8, w) + key[i]) & mask
+        t = 2 * r + 2
+        p, q = P_Q[w]
+        S = [(p + i * q) & mask for i in range(t)]
+        a = b = i = j = 0
+        for _ in range(3 * max(t, c)):
+            a = S[i] = rotl((S[i] + a + b) & mask, 3, w)
+            b = L[j] = rotl((L[j] + a + b) & mask, a + b, w)
+            i, j = (i + 1) % t, (j + 1) % c
+        self.S = S
+
+    def _load(self, block):
+        u = self.u
+        return int.from_bytes(block[:u], "little"), int.from_bytes(block[u:], "little")
+
+    def _store(self, a, b):
+        return a.to_bytes(self.u, "little") + b.to_bytes(self.u, "little")
+
+    def enc(self, block):
+        w, mask, S = self.w, (1 << self.w) - 1, self.S
+        a, b = self._load(block)
+        a, b = (a + S[0]) & mask, (b + S[1]) & mask
+        for i in range(1, self.r + 1):
+            a = (rotl(a ^ b, b, w) + S[2 * i]) & mask
+            b = (rotl(b ^ a, a, w) + S[2 * i + 1]) & mask
+        return self._store(a, b)
+
+    def dec(self, block):
+        w, mask, S = self.w, (1 << self.w) - 1, self.S
+        a, b = self._load(block)
+        for i in range(self.r, 0, -1):
+            b = rotr((b - S[2 * i + 1]) & mask, a, w) ^ a
+            a = rotr((a - S[2 * i]) & mask, b, w) ^ b
+        return self._store((a - S[0]) & mask, (b - S[1]) & mask)
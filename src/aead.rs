@@ -0,0 +1,38 @@
+use crate::{block::xor_bytes, cipher::EncodeBlock, mac::encode_bytes, word::Word};
+
+mod ccm;
+pub use ccm::Ccm;
+
+mod eax;
+pub use eax::Eax;
+
+mod gcm;
+pub use gcm::{Gcm, Gmac};
+
+mod ocb;
+pub use ocb::Ocb;
+
+mod siv;
+pub use siv::Siv;
+
+/// CTR over the whole block as a big-endian counter, starting from `initial_counter`
+pub(crate) fn apply_keystream<W: Word>(
+    cipher: &impl EncodeBlock<W>,
+    initial_counter: &[u8],
+    data: &mut [u8],
+) {
+    let mut counter = initial_counter.to_vec();
+    let mut keystream = vec![0x00; W::BLOCK_BYTES];
+    for chunk in data.chunks_mut(W::BLOCK_BYTES) {
+        keystream.copy_from_slice(&counter);
+        encode_bytes(cipher, &mut keystream);
+        xor_bytes(chunk, &keystream);
+
+        for byte in counter.iter_mut().rev() {
+            *byte = byte.wrapping_add(1);
+            if *byte != 0x00 {
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,348 @@
+use std::marker::PhantomData;
+
+use crate::{
+    block::xor_bytes,
+    cipher::EncodeBlock,
+    mac::{constant_time_eq, encode_bytes},
+    word::Word,
+    Error,
+};
+
+/// Counter with CBC-MAC from [RFC 3610](https://www.rfc-editor.org/rfc/rfc3610)
+/// and [NIST SP 800-38C](https://doi.org/10.6028/NIST.SP.800-38C)
+///
+/// Defined for 128-bit blocks (`u64` words) and, as in some constrained
+/// profiles, for 64-bit blocks (`u32` words) with the same formatting.
+/// The nonce takes the bytes of the block left by the flags and the length field,
+/// so it's `block - 1 - length_size` bytes long and must never repeat with the same key
+pub struct Ccm<W: Word, C: EncodeBlock<W>> {
+    cipher: C,
+    tag_len: usize,
+    length_size: usize,
+    _p: PhantomData<W>,
+}
+
+impl<W: Word, C: EncodeBlock<W>> Ccm<W, C> {
+    /// CCM with `tag_len` bytes of tag and `length_size` bytes
+    /// to encode the length of the message
+    ///
+    /// `Error::WrongBlockSize` - if the block is neither 64 nor 128 bits
+    /// `Error::WrongLengthSize` - if `length_size` isn't from 2 to 8 bytes
+    /// or leaves no room for the nonce
+    /// `Error::WrongTagSize` - if `tag_len` isn't even, from 4 bytes to the block size
+    pub fn new(cipher: C, tag_len: usize, length_size: usize) -> Result<Self, Error> {
+        if W::BLOCK_BYTES != 8 && W::BLOCK_BYTES != 16 {
+            return Err(Error::WrongBlockSize);
+        }
+        if !(2..=8.min(W::BLOCK_BYTES - 2)).contains(&length_size) {
+            return Err(Error::WrongLengthSize);
+        }
+        if tag_len % 2 != 0 || !(4..=W::BLOCK_BYTES).contains(&tag_len) {
+            return Err(Error::WrongTagSize);
+        }
+        Ok(Self {
+            cipher,
+            tag_len,
+            length_size,
+            _p: PhantomData::default(),
+        })
+    }
+
+    /// The only nonce length accepted with this length field size
+    pub fn nonce_len(&self) -> usize {
+        W::BLOCK_BYTES - 1 - self.length_size
+    }
+
+    /// Encrypt `plaintext` and authenticate it together with `associated_data`
+    ///
+    /// Returns the ciphertext followed by the tag
+    /// `Error::WrongNonceSize` - if `nonce` isn't [`Ccm::nonce_len`] bytes long
+    /// `Error::WrongInputSize` - if the length of `plaintext` doesn't fit the length field
+    pub fn encrypt(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let (mut ciphertext, tag) = self.encrypt_detached(nonce, associated_data, plaintext)?;
+        ciphertext.extend(tag);
+        Ok(ciphertext)
+    }
+
+    /// Same as [`Ccm::encrypt`], but returns the ciphertext and the tag separately
+    pub fn encrypt_detached(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        plaintext: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        self.check(nonce, plaintext)?;
+        let tag = self.tag(nonce, associated_data, plaintext);
+        let mut ciphertext = plaintext.to_vec();
+        self.apply_keystream(nonce, &mut ciphertext);
+        Ok((ciphertext, tag))
+    }
+
+    /// Decrypt `ciphertext` and check the tag at its end
+    ///
+    /// `Error::WrongInputSize` - if `ciphertext` is shorter than the tag
+    /// `Error::AuthenticationFailed` - if the tag doesn't match,
+    /// the decrypted data is dropped in that case
+    pub fn decrypt(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let tag_start = ciphertext
+            .len()
+            .checked_sub(self.tag_len)
+            .ok_or(Error::WrongInputSize)?;
+        let (ciphertext, tag) = ciphertext.split_at(tag_start);
+        self.decrypt_detached(nonce, associated_data, ciphertext, tag)
+    }
+
+    /// Same as [`Ccm::decrypt`], but with the tag given separately
+    pub fn decrypt_detached(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+        tag: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        self.check(nonce, ciphertext)?;
+        // The tag is over the plaintext, so it's decrypted first,
+        // but never returned unless the tag matches
+        let mut plaintext = ciphertext.to_vec();
+        self.apply_keystream(nonce, &mut plaintext);
+        match constant_time_eq(&self.tag(nonce, associated_data, &plaintext), tag) {
+            true => Ok(plaintext),
+            false => Err(Error::AuthenticationFailed),
+        }
+    }
+
+    fn check(&self, nonce: &[u8], data: &[u8]) -> Result<(), Error> {
+        if nonce.len() != self.nonce_len() {
+            return Err(Error::WrongNonceSize);
+        }
+        if self.length_size < 8 && (data.len() as u64) >> (8 * self.length_size) != 0 {
+            return Err(Error::WrongInputSize);
+        }
+        Ok(())
+    }
+
+    /// CBC-MAC over `B0`, the associated data and the plaintext,
+    /// encrypted by the block of the zero counter
+    fn tag(&self, nonce: &[u8], associated_data: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        let flags = (u8::from(!associated_data.is_empty()) << 6)
+            | (((self.tag_len as u8 - 2) / 2) << 3)
+            | (self.length_size as u8 - 1);
+        let mut state = self.block(flags, nonce, plaintext.len() as u64);
+        encode_bytes(&self.cipher, &mut state);
+
+        if !associated_data.is_empty() {
+            let len = associated_data.len() as u64;
+            let mut header = match len {
+                len if len < 0xff00 => (len as u16).to_be_bytes().to_vec(),
+                len if len >> 32 == 0 => [&[0xff, 0xfe][..], &(len as u32).to_be_bytes()].concat(),
+                len => [&[0xff, 0xff][..], &len.to_be_bytes()].concat(),
+            };
+            header.extend_from_slice(associated_data);
+            self.absorb(&mut state, &header);
+        }
+        self.absorb(&mut state, plaintext);
+
+        let mut tag = self.counter_block(nonce, 0);
+        xor_bytes(&mut tag, &state);
+        tag.truncate(self.tag_len);
+        tag
+    }
+
+    /// CBC-MAC step for every block of `data`, the last one is padded by zeros
+    fn absorb(&self, state: &mut [u8], data: &[u8]) {
+        for chunk in data.chunks(W::BLOCK_BYTES) {
+            xor_bytes(state, chunk);
+            encode_bytes(&self.cipher, state);
+        }
+    }
+
+    /// CTR starting from the counter 1, the counter 0 masks the tag
+    fn apply_keystream(&self, nonce: &[u8], data: &mut [u8]) {
+        for (index, chunk) in data.chunks_mut(W::BLOCK_BYTES).enumerate() {
+            xor_bytes(chunk, &self.counter_block(nonce, index as u64 + 1));
+        }
+    }
+
+    /// Encoded `A_i` block
+    fn counter_block(&self, nonce: &[u8], index: u64) -> Vec<u8> {
+        let mut block = self.block(self.length_size as u8 - 1, nonce, index);
+        encode_bytes(&self.cipher, &mut block);
+        block
+    }
+
+    /// `flags || nonce || value`, with `value` in the length field
+    fn block(&self, flags: u8, nonce: &[u8], value: u64) -> Vec<u8> {
+        let mut block = Vec::with_capacity(W::BLOCK_BYTES);
+        block.push(flags);
+        block.extend_from_slice(nonce);
+        block.extend_from_slice(&value.to_be_bytes()[8 - self.length_size..]);
+        block
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schedule::KeySchedule, settings::Rc5Settings};
+
+    fn check<W: Word>(ccm: &Ccm<W, &KeySchedule<W>>, ad: &[u8], pt_len: usize, expected: &str) {
+        let expected = hex::decode(expected).unwrap();
+        let nonce = (0xa0..0xa0 + ccm.nonce_len() as u8).collect::<Vec<u8>>();
+        let pt = (0..pt_len as u8).collect::<Vec<u8>>();
+        assert_eq!(ccm.encrypt(&nonce, ad, &pt), Ok(expected.clone()));
+        assert_eq!(ccm.decrypt(&nonce, ad, &expected), Ok(pt));
+    }
+
+    #[test]
+    fn test_vectors() {
+        let key: [u8; 16] = (0..16).collect::<Vec<_>>().try_into().unwrap();
+        let ad = (0x10..0x18).collect::<Vec<u8>>();
+
+        let schedule = KeySchedule::new(key, Rc5Settings::<u64>::new(16));
+        check(
+            &Ccm::new(&schedule, 16, 2).unwrap(),
+            &ad,
+            40,
+            "fcb1fbc5cdfa74c8266fcfeb7523790cef34d82d1ccdf22342ceee8044d7c04c\
+             66aee83019c8c064802cd0ea9044da777ce01ec00053cad5",
+        );
+        check(
+            &Ccm::new(&schedule, 8, 8).unwrap(),
+            &[],
+            20,
+            "75d63539ac518eb7593fb84bc3465d1aa989301869a215a95badb4a0",
+        );
+        check(&Ccm::new(&schedule, 4, 2).unwrap(), &ad, 0, "2b31c2d8");
+
+        // The associated data length takes 6 bytes
+        let long_ad = (0..70000).map(|i| i as u8).collect::<Vec<u8>>();
+        check(
+            &Ccm::new(&schedule, 16, 4).unwrap(),
+            &long_ad,
+            3,
+            "52bd0e81a79436abad907a98d13da2a2484bc1",
+        );
+
+        let schedule = KeySchedule::new(key, Rc5Settings::<u32>::new(12));
+        check(
+            &Ccm::new(&schedule, 8, 2).unwrap(),
+            &ad[..5],
+            20,
+            "3f2b4d58885104d2b3eb6190e3df27d20670e66b66832192390d75d0",
+        );
+        check(
+            &Ccm::new(&schedule, 4, 5).unwrap(),
+            &[],
+            9,
+            "313b7e7b80d25931542a9e7d21",
+        );
+    }
+
+    #[test]
+    fn test_parameters() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u64>::new(16));
+        for (tag_len, length_size) in [(4, 2), (16, 8), (10, 3)] {
+            assert!(Ccm::new(&schedule, tag_len, length_size).is_ok());
+        }
+        for tag_len in [0, 2, 3, 5, 15, 18] {
+            assert!(matches!(
+                Ccm::new(&schedule, tag_len, 2),
+                Err(Error::WrongTagSize)
+            ));
+        }
+        for length_size in [0, 1, 9] {
+            assert!(matches!(
+                Ccm::new(&schedule, 8, length_size),
+                Err(Error::WrongLengthSize)
+            ));
+        }
+        assert_eq!(Ccm::new(&schedule, 8, 2).unwrap().nonce_len(), 13);
+        assert_eq!(Ccm::new(&schedule, 8, 8).unwrap().nonce_len(), 7);
+
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u32>::new(12));
+        assert!(matches!(
+            Ccm::new(&schedule, 10, 2),
+            Err(Error::WrongTagSize)
+        ));
+        assert!(matches!(
+            Ccm::new(&schedule, 8, 7),
+            Err(Error::WrongLengthSize)
+        ));
+        assert_eq!(Ccm::new(&schedule, 8, 6).unwrap().nonce_len(), 1);
+
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u16>::new(12));
+        assert!(matches!(
+            Ccm::new(&schedule, 4, 2),
+            Err(Error::WrongBlockSize)
+        ));
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u128>::new(12));
+        assert!(matches!(
+            Ccm::new(&schedule, 4, 2),
+            Err(Error::WrongBlockSize)
+        ));
+    }
+
+    #[test]
+    fn test_input_checks() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u32>::new(12));
+        let ccm = Ccm::new(&schedule, 4, 2).unwrap();
+        assert_eq!(
+            ccm.encrypt(&[0x00; 4], b"", b""),
+            Err(Error::WrongNonceSize)
+        );
+        assert_eq!(
+            ccm.encrypt(&[0x00; 6], b"", b""),
+            Err(Error::WrongNonceSize)
+        );
+        assert_eq!(
+            ccm.decrypt(&[0x00; 6], b"", &[0x00; 4]),
+            Err(Error::WrongNonceSize)
+        );
+        assert_eq!(
+            ccm.decrypt(&[0x00; 5], b"", &[0x00; 3]),
+            Err(Error::WrongInputSize)
+        );
+        assert_eq!(
+            ccm.encrypt(&[0x00; 5], b"", &vec![0x00; 0x10000]),
+            Err(Error::WrongInputSize)
+        );
+        assert!(ccm.encrypt(&[0x00; 5], b"", &vec![0x00; 0xffff]).is_ok());
+    }
+
+    #[test]
+    fn test_tampering() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u64>::new(16));
+        let ccm = Ccm::new(&schedule, 8, 3).unwrap();
+        let (nonce, ad, pt) = ([0x01; 12], b"header", b"attack at dawn");
+        let ct = ccm.encrypt(&nonce, ad, pt).unwrap();
+
+        for index in 0..ct.len() {
+            let mut tampered = ct.clone();
+            tampered[index] ^= 0x01;
+            assert_eq!(
+                ccm.decrypt(&nonce, ad, &tampered),
+                Err(Error::AuthenticationFailed),
+                "byte {index}"
+            );
+        }
+        assert_eq!(
+            ccm.decrypt(&[0x02; 12], ad, &ct),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(
+            ccm.decrypt(&nonce, b"", &ct),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(ccm.decrypt(&nonce, ad, &ct), Ok(pt.to_vec()));
+    }
+}
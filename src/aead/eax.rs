@@ -0,0 +1,267 @@
+use std::marker::PhantomData;
+
+use crate::{
+    aead::apply_keystream,
+    block::xor_bytes,
+    cipher::EncodeBlock,
+    mac::{constant_time_eq, Cmac},
+    word::Word,
+    Error,
+};
+
+/// EAX authenticated encryption from
+/// [the paper of Bellare, Rogaway & Wagner](https://web.cs.ucdavis.edu/~rogaway/papers/eax.pdf)
+///
+/// CTR encryption with the OMAC (CMAC) of the nonce as the initial counter,
+/// authenticated by OMAC of the nonce, the associated data and the ciphertext.
+/// Works with a block of any size, so with any [`Word`].
+///
+/// The nonce can be of any length, but must never repeat with the same key
+pub struct Eax<W: Word, C: EncodeBlock<W>> {
+    cipher: C,
+    tag_len: usize,
+    _p: PhantomData<W>,
+}
+
+impl<W: Word, C: EncodeBlock<W>> Eax<W, C> {
+    /// EAX with the full-block tag
+    pub fn new(cipher: C) -> Self {
+        Self {
+            cipher,
+            tag_len: W::BLOCK_BYTES,
+            _p: PhantomData::default(),
+        }
+    }
+
+    /// EAX with the tag truncated to `tag_len` bytes
+    ///
+    /// `Error` - if `tag_len` is zero or more than the block size
+    pub fn with_tag_len(cipher: C, tag_len: usize) -> Result<Self, Error> {
+        if tag_len == 0 || tag_len > W::BLOCK_BYTES {
+            return Err(Error::WrongTagSize);
+        }
+        Ok(Self {
+            tag_len,
+            ..Self::new(cipher)
+        })
+    }
+
+    /// Encrypt `plaintext` and authenticate it together with `associated_data`
+    ///
+    /// Returns the ciphertext followed by the tag
+    pub fn encrypt(&self, nonce: &[u8], associated_data: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        let (mut ciphertext, tag) = self.encrypt_detached(nonce, associated_data, plaintext);
+        ciphertext.extend(tag);
+        ciphertext
+    }
+
+    /// Same as [`Eax::encrypt`], but returns the ciphertext and the tag separately
+    pub fn encrypt_detached(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        plaintext: &[u8],
+    ) -> (Vec<u8>, Vec<u8>) {
+        let nonce_mac = self.omac(0, nonce);
+        let mut ciphertext = plaintext.to_vec();
+        apply_keystream(&self.cipher, &nonce_mac, &mut ciphertext);
+        let tag = self.tag(nonce_mac, associated_data, &ciphertext);
+        (ciphertext, tag)
+    }
+
+    /// Check the tag at the end of `ciphertext` and decrypt it
+    ///
+    /// `Error::WrongInputSize` - if `ciphertext` is shorter than the tag
+    /// `Error::AuthenticationFailed` - if the tag doesn't match,
+    /// nothing is decrypted in that case
+    pub fn decrypt(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let tag_start = ciphertext
+            .len()
+            .checked_sub(self.tag_len)
+            .ok_or(Error::WrongInputSize)?;
+        let (ciphertext, tag) = ciphertext.split_at(tag_start);
+        self.decrypt_detached(nonce, associated_data, ciphertext, tag)
+    }
+
+    /// Same as [`Eax::decrypt`], but with the tag given separately
+    ///
+    /// `Error::AuthenticationFailed` - if the tag doesn't match,
+    /// nothing is decrypted in that case
+    pub fn decrypt_detached(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+        tag: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let nonce_mac = self.omac(0, nonce);
+        let expected_tag = self.tag(nonce_mac.clone(), associated_data, ciphertext);
+        if !constant_time_eq(&expected_tag, tag) {
+            return Err(Error::AuthenticationFailed);
+        }
+
+        let mut plaintext = ciphertext.to_vec();
+        apply_keystream(&self.cipher, &nonce_mac, &mut plaintext);
+        Ok(plaintext)
+    }
+
+    /// `OMAC^t(input)`: OMAC of the block encoding `t` followed by `input`
+    fn omac(&self, t: u8, input: &[u8]) -> Vec<u8> {
+        let mut tweak = vec![0x00; W::BLOCK_BYTES];
+        tweak[W::BLOCK_BYTES - 1] = t;
+
+        let mut cmac = Cmac::new(&self.cipher);
+        cmac.update(&tweak);
+        cmac.update(input);
+        cmac.finalize()
+    }
+
+    /// `N ^ H ^ C` truncated to the tag length
+    fn tag(&self, mut nonce_mac: Vec<u8>, associated_data: &[u8], ciphertext: &[u8]) -> Vec<u8> {
+        xor_bytes(&mut nonce_mac, &self.omac(1, associated_data));
+        xor_bytes(&mut nonce_mac, &self.omac(2, ciphertext));
+        nonce_mac.truncate(self.tag_len);
+        nonce_mac
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schedule::KeySchedule, settings::Rc5Settings};
+
+    fn check<W: Word>(schedule: &KeySchedule<W>, nonce_len: usize, ad_len: usize, expected: &str) {
+        let expected = hex::decode(expected).unwrap();
+        let nonce = (0xa0..0xa0 + nonce_len as u8).collect::<Vec<u8>>();
+        let ad = (0x10..0x10 + ad_len as u8).collect::<Vec<u8>>();
+        let pt = (0..(expected.len() - W::BLOCK_BYTES) as u8).collect::<Vec<u8>>();
+
+        let eax = Eax::new(schedule);
+        assert_eq!(eax.encrypt(&nonce, &ad, &pt), expected);
+        assert_eq!(eax.decrypt(&nonce, &ad, &expected), Ok(pt));
+    }
+
+    #[test]
+    fn test_vectors() {
+        let key: [u8; 16] = (0..16).collect::<Vec<_>>().try_into().unwrap();
+
+        let schedule = KeySchedule::new(key, Rc5Settings::default());
+        check(
+            &schedule,
+            8,
+            5,
+            "051b8015e7a341e537dab3cb5e5a102776670f720677e6b0b1c395ec",
+        );
+        check(&schedule, 16, 0, "f9ec750ba46790ae");
+
+        let schedule = KeySchedule::new(key, Rc5Settings::<u64>::new(16));
+        check(
+            &schedule,
+            16,
+            8,
+            "b31b465d2abc67980abafd97889f4bcf00d3250706180592f045530a0680b198\
+             c23ea4bee9243b29dc3c3b74e22f6e3c52bab2f10b1ffcc2",
+        );
+
+        let schedule = KeySchedule::new([0x00, 0x01, 0x02, 0x03], Rc5Settings::<u16>::new(12));
+        check(&schedule, 4, 3, "26f952c7cbce968dc8543eb375");
+        let schedule = KeySchedule::new([0x00, 0x01, 0x02, 0x03], Rc5Settings::<u8>::new(12));
+        check(&schedule, 3, 2, "0a593a8ef2c84d");
+
+        let schedule = KeySchedule::new(key, Rc5Settings::<u128>::new(20));
+        check(
+            &schedule,
+            32,
+            10,
+            "8b2740fb91c8b2009456f504f3cdde1862a923a2f0c51df3be8c34bd3aea8a96\
+             885818cba0369f8c23c18d14fcaec7bbcf2df7141b5f679f3d34995af9c978ea\
+             90d58ced39e9bea1d71d7b6c2305a6a56275",
+        );
+    }
+
+    #[test]
+    fn test_truncated_tag() {
+        let key: [u8; 16] = (0..16).collect::<Vec<_>>().try_into().unwrap();
+        let schedule = KeySchedule::new(key, Rc5Settings::default());
+        let eax = Eax::with_tag_len(&schedule, 4).unwrap();
+        let nonce = (0xa0..0xa8).collect::<Vec<u8>>();
+        let ad = (0x10..0x15).collect::<Vec<u8>>();
+        let pt = (0..20).collect::<Vec<u8>>();
+
+        let ct = eax.encrypt(&nonce, &ad, &pt);
+        assert_eq!(
+            hex::encode(&ct),
+            "051b8015e7a341e537dab3cb5e5a102776670f720677e6b0"
+        );
+        assert_eq!(eax.decrypt(&nonce, &ad, &ct), Ok(pt));
+
+        assert!(matches!(
+            Eax::with_tag_len(&schedule, 0),
+            Err(Error::WrongTagSize)
+        ));
+        assert!(matches!(
+            Eax::with_tag_len(&schedule, 9),
+            Err(Error::WrongTagSize)
+        ));
+    }
+
+    #[test]
+    fn test_tampering() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u64>::new(16));
+        let eax = Eax::new(&schedule);
+        let (nonce, ad, pt) = ([0x01; 12], b"header", b"attack at dawn");
+        let ct = eax.encrypt(&nonce, ad, pt);
+
+        for index in 0..ct.len() {
+            let mut tampered = ct.clone();
+            tampered[index] ^= 0x01;
+            assert_eq!(
+                eax.decrypt(&nonce, ad, &tampered),
+                Err(Error::AuthenticationFailed),
+                "byte {index}"
+            );
+        }
+        assert_eq!(
+            eax.decrypt(&[0x02; 12], ad, &ct),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(
+            eax.decrypt(&nonce, b"Header", &ct),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(
+            eax.decrypt(&nonce, ad, &ct[..ct.len() - 1]),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(
+            eax.decrypt(&nonce, ad, &ct[..15]),
+            Err(Error::WrongInputSize)
+        );
+        assert_eq!(eax.decrypt(&nonce, ad, &ct), Ok(pt.to_vec()));
+    }
+
+    #[test]
+    fn test_detached() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::default());
+        let eax = Eax::new(&schedule);
+        let (ct, tag) = eax.encrypt_detached(b"nonce", b"", b"message");
+        assert_eq!(ct.len(), 7);
+        assert_eq!(
+            [ct.clone(), tag.clone()].concat(),
+            eax.encrypt(b"nonce", b"", b"message")
+        );
+        assert_eq!(
+            eax.decrypt_detached(b"nonce", b"", &ct, &tag),
+            Ok(b"message".to_vec())
+        );
+        assert_eq!(
+            eax.decrypt_detached(b"nonce", b"", &ct, &tag[..4]),
+            Err(Error::AuthenticationFailed)
+        );
+    }
+}
@@ -0,0 +1,400 @@
+use crate::{
+    block::xor_bytes,
+    cipher::EncodeBlock,
+    mac::{constant_time_eq, encode_bytes},
+    Error,
+};
+
+/// Block size of RC5 with `u64` words, the only one GCM is defined for
+const BLOCK_BYTES: usize = 16;
+
+/// `(2^32 - 2)` blocks: the 32-bit counter must not wrap into `J0`
+const MAX_INPUT_LEN: u64 = ((1 << 32) - 2) * BLOCK_BYTES as u64;
+
+/// `x^128 + x^7 + x^2 + x + 1` in the bit-reflected order of GCM
+const REDUCTION: u128 = 0xe1 << 120;
+
+/// Galois/Counter Mode from [NIST SP 800-38D](https://doi.org/10.6028/NIST.SP.800-38D)
+///
+/// GCM needs a 128-bit block, so it's implemented only for RC5 with `u64` words.
+/// Any other word is rejected at compile time:
+/// ```compile_fail
+/// use rc5_cypher::*;
+///
+/// let schedule = KeySchedule::new([0x00; 16], Rc5Settings::<u32>::new(12));
+/// Gcm::new(&schedule);
+/// ```
+/// A 12-byte nonce is the common choice, others are hashed into the counter.
+/// The nonce must never repeat with the same key
+pub struct Gcm<C: EncodeBlock<u64>> {
+    cipher: C,
+    /// Hash key `H = E(0^128)`
+    hash_key: u128,
+    tag_len: usize,
+}
+
+impl<C: EncodeBlock<u64>> Gcm<C> {
+    /// GCM with the full 16-byte tag
+    pub fn new(cipher: C) -> Self {
+        let mut hash_key = [0x00; BLOCK_BYTES];
+        encode_bytes(&cipher, &mut hash_key);
+        Self {
+            cipher,
+            hash_key: u128::from_be_bytes(hash_key),
+            tag_len: BLOCK_BYTES,
+        }
+    }
+
+    /// GCM with the tag truncated to `tag_len` bytes
+    ///
+    /// `Error` - if `tag_len` isn't 4, 8 or 12..=16, as allowed by the specification
+    pub fn with_tag_len(cipher: C, tag_len: usize) -> Result<Self, Error> {
+        if !matches!(tag_len, 4 | 8 | 12..=16) {
+            return Err(Error::WrongTagSize);
+        }
+        Ok(Self {
+            tag_len,
+            ..Self::new(cipher)
+        })
+    }
+
+    /// Encrypt `plaintext` and authenticate it together with `associated_data`
+    ///
+    /// Returns the ciphertext followed by the tag
+    /// `Error::WrongNonceSize` - if `nonce` is empty
+    /// `Error::WrongInputSize` - if `plaintext` is longer than `2^32 - 2` blocks
+    pub fn encrypt(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let (mut ciphertext, tag) = self.encrypt_detached(nonce, associated_data, plaintext)?;
+        ciphertext.extend(tag);
+        Ok(ciphertext)
+    }
+
+    /// Same as [`Gcm::encrypt`], but returns the ciphertext and the tag separately
+    pub fn encrypt_detached(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        plaintext: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        let initial_counter = self.initial_counter(nonce)?;
+        if plaintext.len() as u64 > MAX_INPUT_LEN {
+            return Err(Error::WrongInputSize);
+        }
+
+        let mut ciphertext = plaintext.to_vec();
+        self.apply_keystream(initial_counter, &mut ciphertext);
+        let tag = self.tag(initial_counter, associated_data, &ciphertext);
+        Ok((ciphertext, tag))
+    }
+
+    /// Check the tag at the end of `ciphertext` and decrypt it
+    ///
+    /// `Error::WrongInputSize` - if `ciphertext` is shorter than the tag
+    /// `Error::AuthenticationFailed` - if the tag doesn't match,
+    /// nothing is decrypted in that case
+    pub fn decrypt(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let tag_start = ciphertext
+            .len()
+            .checked_sub(self.tag_len)
+            .ok_or(Error::WrongInputSize)?;
+        let (ciphertext, tag) = ciphertext.split_at(tag_start);
+        self.decrypt_detached(nonce, associated_data, ciphertext, tag)
+    }
+
+    /// Same as [`Gcm::decrypt`], but with the tag given separately
+    pub fn decrypt_detached(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+        tag: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let initial_counter = self.initial_counter(nonce)?;
+        if ciphertext.len() as u64 > MAX_INPUT_LEN {
+            return Err(Error::WrongInputSize);
+        }
+
+        let expected_tag = self.tag(initial_counter, associated_data, ciphertext);
+        if !constant_time_eq(&expected_tag, tag) {
+            return Err(Error::AuthenticationFailed);
+        }
+
+        let mut plaintext = ciphertext.to_vec();
+        self.apply_keystream(initial_counter, &mut plaintext);
+        Ok(plaintext)
+    }
+
+    /// `J0`: the nonce with a counter of 1 for 12-byte nonces, GHASH of the nonce otherwise
+    fn initial_counter(&self, nonce: &[u8]) -> Result<[u8; BLOCK_BYTES], Error> {
+        if nonce.is_empty() {
+            return Err(Error::WrongNonceSize);
+        }
+        if nonce.len() == 12 {
+            let mut counter = [0x00; BLOCK_BYTES];
+            counter[..12].copy_from_slice(nonce);
+            counter[BLOCK_BYTES - 1] = 0x01;
+            return Ok(counter);
+        }
+
+        let mut ghash = Ghash::new(self.hash_key);
+        ghash.update_padded(nonce);
+        ghash.update_block((nonce.len() as u128) * 8);
+        Ok(ghash.state.to_be_bytes())
+    }
+
+    /// `E(J0) ^ GHASH(A, C)` truncated to the tag length
+    fn tag(
+        &self,
+        mut initial_counter: [u8; BLOCK_BYTES],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+    ) -> Vec<u8> {
+        let mut ghash = Ghash::new(self.hash_key);
+        ghash.update_padded(associated_data);
+        ghash.update_padded(ciphertext);
+        ghash.update_block(
+            ((associated_data.len() as u128 * 8) << 64) | (ciphertext.len() as u128 * 8),
+        );
+
+        encode_bytes(&self.cipher, &mut initial_counter);
+        xor_bytes(&mut initial_counter, &ghash.state.to_be_bytes());
+        initial_counter[..self.tag_len].to_vec()
+    }
+
+    /// CTR with the last 32 bits of the block as a big-endian counter, starting after `J0`
+    fn apply_keystream(&self, mut counter: [u8; BLOCK_BYTES], data: &mut [u8]) {
+        for chunk in data.chunks_mut(BLOCK_BYTES) {
+            let low = u32::from_be_bytes(counter[12..].try_into().unwrap());
+            counter[12..].copy_from_slice(&low.wrapping_add(1).to_be_bytes());
+
+            let mut keystream = counter;
+            encode_bytes(&self.cipher, &mut keystream);
+            xor_bytes(chunk, &keystream);
+        }
+    }
+}
+
+/// GMAC: GCM with only associated data, a nonce-based MAC
+///
+/// Same restrictions as [`Gcm`]: `u64` words only, the nonce must never repeat
+pub struct Gmac<C: EncodeBlock<u64>> {
+    gcm: Gcm<C>,
+}
+
+impl<C: EncodeBlock<u64>> Gmac<C> {
+    /// GMAC with the full 16-byte tag
+    pub fn new(cipher: C) -> Self {
+        Self {
+            gcm: Gcm::new(cipher),
+        }
+    }
+
+    /// GMAC with the tag truncated to `tag_len` bytes, check [`Gcm::with_tag_len`]
+    pub fn with_tag_len(cipher: C, tag_len: usize) -> Result<Self, Error> {
+        Ok(Self {
+            gcm: Gcm::with_tag_len(cipher, tag_len)?,
+        })
+    }
+
+    /// Tag of the `message`
+    ///
+    /// `Error::WrongNonceSize` - if `nonce` is empty
+    pub fn compute(&self, nonce: &[u8], message: &[u8]) -> Result<Vec<u8>, Error> {
+        let initial_counter = self.gcm.initial_counter(nonce)?;
+        Ok(self.gcm.tag(initial_counter, message, &[]))
+    }
+
+    /// Check the tag of the `message` in constant time
+    ///
+    /// `Error::AuthenticationFailed` - if the tag doesn't match
+    pub fn verify(&self, nonce: &[u8], message: &[u8], tag: &[u8]) -> Result<(), Error> {
+        match constant_time_eq(&self.compute(nonce, message)?, tag) {
+            true => Ok(()),
+            false => Err(Error::AuthenticationFailed),
+        }
+    }
+}
+
+/// GHASH over the blocks given so far
+struct Ghash {
+    hash_key: u128,
+    state: u128,
+}
+
+impl Ghash {
+    fn new(hash_key: u128) -> Self {
+        Self { hash_key, state: 0 }
+    }
+
+    fn update_block(&mut self, block: u128) {
+        self.state = gf_mul(self.state ^ block, self.hash_key);
+    }
+
+    /// Hash `data` with the last block padded by zeros
+    fn update_padded(&mut self, data: &[u8]) {
+        for chunk in data.chunks(BLOCK_BYTES) {
+            let mut block = [0x00; BLOCK_BYTES];
+            block[..chunk.len()].copy_from_slice(chunk);
+            self.update_block(u128::from_be_bytes(block));
+        }
+    }
+}
+
+/// Multiplication in `GF(2^128)` as defined by GCM
+///
+/// Masks instead of branches and table lookups, so the time doesn't depend
+/// on the hash key or the data
+fn gf_mul(x: u128, y: u128) -> u128 {
+    let mut product = 0;
+    let mut addend = y;
+    for bit in (0..128).rev() {
+        product ^= addend & 0u128.wrapping_sub((x >> bit) & 1);
+        addend = (addend >> 1) ^ (REDUCTION & 0u128.wrapping_sub(addend & 1));
+    }
+    product
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schedule::KeySchedule, settings::Rc5Settings};
+
+    fn schedule() -> KeySchedule<u64> {
+        let key: [u8; 16] = (0..16).collect::<Vec<_>>().try_into().unwrap();
+        KeySchedule::new(key, Rc5Settings::new(16))
+    }
+
+    #[test]
+    fn test_vectors() {
+        let schedule = schedule();
+        let gcm = Gcm::new(&schedule);
+        let nonce = (0xa0..0xac).collect::<Vec<u8>>();
+        let ad = (0x10..0x18).collect::<Vec<u8>>();
+        let pt = (0..40).collect::<Vec<u8>>();
+
+        let ct = hex::decode(
+            "5640343bef405594dc94d76d62440e31a6b092e96a0a6eb37481fdee838630e0\
+             7efc8a24a83738a39b4cc11c7a40cac47cc7cba8b4a40941",
+        )
+        .unwrap();
+        assert_eq!(gcm.encrypt(&nonce, &ad, &pt), Ok(ct.clone()));
+        assert_eq!(gcm.decrypt(&nonce, &ad, &ct), Ok(pt.clone()));
+
+        assert_eq!(
+            hex::encode(gcm.encrypt(&nonce, &[], &[]).unwrap()),
+            "19d49985a36f152b4a67e02bd75ee55b"
+        );
+
+        // Not a 12-byte nonce, so it's hashed
+        let ct = gcm.encrypt(&nonce[..8], &ad, &pt[..17]).unwrap();
+        assert_eq!(
+            hex::encode(&ct),
+            "27c6ee9c5a42e9172270623734fb47117c185622e13360c17f2222bb6e01176f26"
+        );
+        assert_eq!(gcm.decrypt(&nonce[..8], &ad, &ct), Ok(pt[..17].to_vec()));
+    }
+
+    #[test]
+    fn test_tag_len() {
+        let schedule = schedule();
+        let nonce = (0xa0..0xac).collect::<Vec<u8>>();
+        let ad = (0x10..0x18).collect::<Vec<u8>>();
+        let pt = (0..40).collect::<Vec<u8>>();
+        let full = Gcm::new(&schedule).encrypt(&nonce, &ad, &pt).unwrap();
+
+        for tag_len in [4, 8, 12, 13, 14, 15, 16] {
+            let gcm = Gcm::with_tag_len(&schedule, tag_len).unwrap();
+            let ct = gcm.encrypt(&nonce, &ad, &pt).unwrap();
+            assert_eq!(ct, full[..pt.len() + tag_len]);
+            assert_eq!(gcm.decrypt(&nonce, &ad, &ct), Ok(pt.clone()));
+        }
+        for tag_len in [0, 1, 5, 11, 17] {
+            assert!(matches!(
+                Gcm::with_tag_len(&schedule, tag_len),
+                Err(Error::WrongTagSize)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_tampering() {
+        let schedule = schedule();
+        let gcm = Gcm::new(&schedule);
+        let (nonce, ad, pt) = ([0x01; 12], b"header", b"attack at dawn");
+        let ct = gcm.encrypt(&nonce, ad, pt).unwrap();
+
+        for index in 0..ct.len() {
+            let mut tampered = ct.clone();
+            tampered[index] ^= 0x80;
+            assert_eq!(
+                gcm.decrypt(&nonce, ad, &tampered),
+                Err(Error::AuthenticationFailed),
+                "byte {index}"
+            );
+        }
+        assert_eq!(
+            gcm.decrypt(&[0x02; 12], ad, &ct),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(
+            gcm.decrypt(&nonce, b"Header", &ct),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(
+            gcm.decrypt(&nonce, ad, &ct[..15]),
+            Err(Error::WrongInputSize)
+        );
+        assert_eq!(gcm.encrypt(&[], ad, pt), Err(Error::WrongNonceSize));
+        assert_eq!(gcm.decrypt(&[], ad, &ct), Err(Error::WrongNonceSize));
+    }
+
+    #[test]
+    fn test_gmac() {
+        let schedule = schedule();
+        let gmac = Gmac::new(&schedule);
+        let nonce = (0xa0..0xac).collect::<Vec<u8>>();
+        let message = [
+            (0x10..0x18).collect::<Vec<u8>>(),
+            (0..40).collect::<Vec<u8>>(),
+        ]
+        .concat();
+
+        let tag = gmac.compute(&nonce, &message).unwrap();
+        assert_eq!(hex::encode(&tag), "093a703961ad919c7acb44e4068c1cf3");
+        assert_eq!(gmac.verify(&nonce, &message, &tag), Ok(()));
+        assert_eq!(
+            gmac.verify(&nonce, &message[1..], &tag),
+            Err(Error::AuthenticationFailed)
+        );
+
+        let gmac = Gmac::with_tag_len(&schedule, 8).unwrap();
+        assert_eq!(gmac.verify(&nonce, &message, &tag[..8]), Ok(()));
+        assert_eq!(
+            gmac.verify(&nonce, &message, &tag),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(gmac.compute(&[], &message), Err(Error::WrongNonceSize));
+    }
+
+    #[test]
+    fn test_gf_mul() {
+        // `1` is the most significant bit in the reflected order
+        let one = 1 << 127;
+        let value = 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210;
+        assert_eq!(gf_mul(value, one), value);
+        assert_eq!(gf_mul(one, value), value);
+        assert_eq!(gf_mul(value, 0), 0);
+        // `x^127 * x = x^128 = x^7 + x^2 + x + 1`
+        assert_eq!(gf_mul(1, 1 << 126), REDUCTION);
+    }
+}
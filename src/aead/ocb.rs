@@ -0,0 +1,402 @@
+use crate::{
+    cipher::{DecodeBlock, EncodeBlock},
+    mac::{constant_time_eq, decode_bytes, double, encode_bytes},
+    Error,
+};
+
+/// Block size of RC5 with `u64` words, the only one OCB3 is defined for
+const BLOCK_BYTES: usize = 16;
+
+/// Offset Codebook mode, version 3, from [RFC 7253](https://www.rfc-editor.org/rfc/rfc7253)
+///
+/// Single pass: about one block cipher call per block of the plaintext and
+/// of the associated data. The offset of every block is computed from its index
+/// alone, so blocks don't depend on each other.
+///
+/// OCB3 needs a 128-bit block, so it's implemented only for RC5 with `u64` words:
+/// ```compile_fail
+/// use rc5_cypher::*;
+///
+/// let schedule = KeySchedule::new([0x00; 16], Rc5Settings::<u32>::new(12));
+/// Ocb::new(&schedule);
+/// ```
+/// The nonce is 1 to 15 bytes and must never repeat with the same key
+pub struct Ocb<C: EncodeBlock<u64>> {
+    cipher: C,
+    tag_len: usize,
+    /// `L_*`
+    l_star: u128,
+    /// `L_$`
+    l_dollar: u128,
+    /// `L_i`, one for every possible count of trailing zeros of a block index
+    l: Vec<u128>,
+}
+
+impl<C: EncodeBlock<u64>> Ocb<C> {
+    /// OCB3 with the full 16-byte tag
+    pub fn new(cipher: C) -> Self {
+        let mut block = [0x00; BLOCK_BYTES];
+        encode_bytes(&cipher, &mut block);
+        let l_star = u128::from_be_bytes(block);
+
+        double(&mut block);
+        let l_dollar = u128::from_be_bytes(block);
+        let l = (0..usize::BITS)
+            .map(|_| {
+                double(&mut block);
+                u128::from_be_bytes(block)
+            })
+            .collect();
+
+        Self {
+            cipher,
+            tag_len: BLOCK_BYTES,
+            l_star,
+            l_dollar,
+            l,
+        }
+    }
+
+    /// OCB3 with the tag truncated to `tag_len` bytes
+    ///
+    /// `Error` - if `tag_len` is zero or more than 16
+    pub fn with_tag_len(cipher: C, tag_len: usize) -> Result<Self, Error> {
+        if tag_len == 0 || tag_len > BLOCK_BYTES {
+            return Err(Error::WrongTagSize);
+        }
+        Ok(Self {
+            tag_len,
+            ..Self::new(cipher)
+        })
+    }
+
+    /// Encrypt `plaintext` and authenticate it together with `associated_data`
+    ///
+    /// Returns the ciphertext followed by the tag
+    /// `Error::WrongNonceSize` - if `nonce` isn't 1 to 15 bytes long
+    pub fn encrypt(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let initial_offset = self.initial_offset(nonce)?;
+        let (full, last) = split_blocks(plaintext);
+
+        let mut ciphertext = Vec::with_capacity(plaintext.len() + self.tag_len);
+        let mut checksum = 0;
+        for (index, block) in full.chunks(BLOCK_BYTES).enumerate() {
+            let block = u128::from_be_bytes(block.try_into().unwrap());
+            let offset = self.offset(initial_offset, index + 1);
+            ciphertext.extend((self.encode(block ^ offset) ^ offset).to_be_bytes());
+            checksum ^= block;
+        }
+
+        let mut offset = self.offset(initial_offset, full.len() / BLOCK_BYTES);
+        if !last.is_empty() {
+            offset ^= self.l_star;
+            let pad = self.encode(offset).to_be_bytes();
+            ciphertext.extend(last.iter().zip(pad).map(|(byte, pad)| byte ^ pad));
+            checksum ^= pad_block(last);
+        }
+
+        let tag = self.encode(checksum ^ offset ^ self.l_dollar) ^ self.hash(associated_data);
+        ciphertext.extend_from_slice(&tag.to_be_bytes()[..self.tag_len]);
+        Ok(ciphertext)
+    }
+
+    /// `Offset_0`, derived from the nonce and the tag length
+    fn initial_offset(&self, nonce: &[u8]) -> Result<u128, Error> {
+        if nonce.is_empty() || nonce.len() >= BLOCK_BYTES {
+            return Err(Error::WrongNonceSize);
+        }
+        let mut block = [0x00; BLOCK_BYTES];
+        block[BLOCK_BYTES - nonce.len()..].copy_from_slice(nonce);
+        block[BLOCK_BYTES - nonce.len() - 1] |= 0x01;
+        block[0] |= ((self.tag_len * 8 % 128) as u8) << 1;
+        let block = u128::from_be_bytes(block);
+
+        let bottom = (block & 0x3f) as u32;
+        let top = self.encode(block & !0x3f);
+        let stretch = ((top >> 64) as u64) ^ ((top >> 56) as u64);
+        Ok((top << bottom) | ((stretch as u128) >> (64 - bottom)))
+    }
+
+    /// `Offset_i = Offset_0 ^ L_ntz(1) ^ ... ^ L_ntz(i)`,
+    /// the same as the XOR of `L_j` for every bit `j` of the Gray code of `i`
+    fn offset(&self, initial_offset: u128, index: usize) -> u128 {
+        let gray = index ^ (index >> 1);
+        self.l
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| (gray >> bit) & 1 == 1)
+            .fold(initial_offset, |offset, (_, l)| offset ^ l)
+    }
+
+    /// `HASH(K, A)`: the associated data part of the tag
+    fn hash(&self, associated_data: &[u8]) -> u128 {
+        let (full, last) = split_blocks(associated_data);
+        let mut sum = full
+            .chunks(BLOCK_BYTES)
+            .enumerate()
+            .map(|(index, block)| {
+                let block = u128::from_be_bytes(block.try_into().unwrap());
+                self.encode(block ^ self.offset(0, index + 1))
+            })
+            .fold(0, |sum, encoded| sum ^ encoded);
+
+        if !last.is_empty() {
+            let offset = self.offset(0, full.len() / BLOCK_BYTES) ^ self.l_star;
+            sum ^= self.encode(pad_block(last) ^ offset);
+        }
+        sum
+    }
+
+    fn encode(&self, block: u128) -> u128 {
+        let mut bytes = block.to_be_bytes();
+        encode_bytes(&self.cipher, &mut bytes);
+        u128::from_be_bytes(bytes)
+    }
+}
+
+impl<C: EncodeBlock<u64> + DecodeBlock<u64>> Ocb<C> {
+    /// Check the tag at the end of `ciphertext` and decrypt it
+    ///
+    /// `Error::WrongNonceSize` - if `nonce` isn't 1 to 15 bytes long
+    /// `Error::WrongInputSize` - if `ciphertext` is shorter than the tag
+    /// `Error::AuthenticationFailed` - if the tag doesn't match,
+    /// the decrypted data is dropped in that case
+    pub fn decrypt(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let initial_offset = self.initial_offset(nonce)?;
+        let tag_start = ciphertext
+            .len()
+            .checked_sub(self.tag_len)
+            .ok_or(Error::WrongInputSize)?;
+        let (ciphertext, tag) = ciphertext.split_at(tag_start);
+        let (full, last) = split_blocks(ciphertext);
+
+        let mut plaintext = Vec::with_capacity(ciphertext.len());
+        let mut checksum = 0;
+        for (index, block) in full.chunks(BLOCK_BYTES).enumerate() {
+            let block = u128::from_be_bytes(block.try_into().unwrap());
+            let offset = self.offset(initial_offset, index + 1);
+            let decoded = self.decode(block ^ offset) ^ offset;
+            plaintext.extend(decoded.to_be_bytes());
+            checksum ^= decoded;
+        }
+
+        let mut offset = self.offset(initial_offset, full.len() / BLOCK_BYTES);
+        if !last.is_empty() {
+            offset ^= self.l_star;
+            let pad = self.encode(offset).to_be_bytes();
+            let start = plaintext.len();
+            plaintext.extend(last.iter().zip(pad).map(|(byte, pad)| byte ^ pad));
+            checksum ^= pad_block(&plaintext[start..]);
+        }
+
+        let expected_tag =
+            self.encode(checksum ^ offset ^ self.l_dollar) ^ self.hash(associated_data);
+        match constant_time_eq(&expected_tag.to_be_bytes()[..self.tag_len], tag) {
+            true => Ok(plaintext),
+            false => Err(Error::AuthenticationFailed),
+        }
+    }
+
+    fn decode(&self, block: u128) -> u128 {
+        let mut bytes = block.to_be_bytes();
+        decode_bytes(&self.cipher, &mut bytes);
+        u128::from_be_bytes(bytes)
+    }
+}
+
+/// Split into full blocks and the incomplete last one
+fn split_blocks(data: &[u8]) -> (&[u8], &[u8]) {
+    data.split_at(data.len() - data.len() % BLOCK_BYTES)
+}
+
+/// `data || 1 || 0...` for an incomplete block
+fn pad_block(data: &[u8]) -> u128 {
+    let mut block = [0x00; BLOCK_BYTES];
+    block[..data.len()].copy_from_slice(data);
+    block[data.len()] = 0x80;
+    u128::from_be_bytes(block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schedule::KeySchedule, settings::Rc5Settings};
+
+    fn schedule() -> KeySchedule<u64> {
+        let key: [u8; 16] = (0..16).collect::<Vec<_>>().try_into().unwrap();
+        KeySchedule::new(key, Rc5Settings::new(16))
+    }
+
+    fn check(
+        ocb: &Ocb<&KeySchedule<u64>>,
+        nonce_len: usize,
+        ad_len: usize,
+        pt_len: usize,
+        expected: &str,
+    ) {
+        let expected = hex::decode(expected).unwrap();
+        let nonce = (0xa0..0xa0 + nonce_len as u8).collect::<Vec<u8>>();
+        let ad = (0x10..0x10 + ad_len as u8).collect::<Vec<u8>>();
+        let pt = (0..pt_len as u8).collect::<Vec<u8>>();
+        assert_eq!(ocb.encrypt(&nonce, &ad, &pt), Ok(expected.clone()));
+        assert_eq!(ocb.decrypt(&nonce, &ad, &expected), Ok(pt));
+    }
+
+    #[test]
+    fn test_vectors() {
+        let schedule = schedule();
+        let ocb = Ocb::new(&schedule);
+        check(
+            &ocb,
+            12,
+            8,
+            40,
+            "182f7f341a6c929dcf1954a05a9311238de4293afc479a933d78e2c2e2475778\
+             6fe4c2cc70f4c412ffd5cf91cc1b4d892f577f75b3be5666",
+        );
+        check(&ocb, 12, 0, 0, "7b367ce093b00e4e234cf07b73779e65");
+        check(
+            &ocb,
+            7,
+            33,
+            32,
+            "82b9ee4e97c111710c37cdd7e61ed434245b32233fd6b0f2326bb3bdf0c5756a\
+             7de5bdaf0171eb64e4324ce1e4bcefe9",
+        );
+        check(
+            &ocb,
+            15,
+            8,
+            200,
+            "559070e3319a3cc85a3cd4cf57a16684eb0d2ae57356d5a221c9df982bfd92c7\
+             250749d2dd80044906b06bc02bbdcbf1ea15f2cff5fb8909383b508518955e78\
+             9f729acfe535a8d81b0c370511c69ecc64c64d2cff771e2649207c354b9b27b8\
+             8b2ccae9d99b0fd13512e7794f0f498364b8f9e311956710dc2637c0231cc56d\
+             30b668c5acd0183bbbc58ff0c7e0d1ad6c30617aca8369d3ad27378948ded351\
+             736266d8a4edd1620df84aa80ceb0a31796e4cc48b89e5305e5d76381570b06f\
+             9062fd7642b8b3887d7e12ebc3d7176df0f326ebc4d7dd98",
+        );
+
+        // The tag length is a part of the nonce block, so it's not just truncated
+        let ocb = Ocb::with_tag_len(&schedule, 8).unwrap();
+        check(
+            &ocb,
+            12,
+            8,
+            40,
+            "911f4d3ad63d0340f14e4b5742cedd1e8d0d1fbf3ed84f6eb22dabe06018093c\
+             fe4fdf0c3c5b2927824ac60815eab121",
+        );
+    }
+
+    #[test]
+    fn test_offset() {
+        let schedule = schedule();
+        let ocb = Ocb::new(&schedule);
+        let mut offset = 0x42;
+        for index in 1..1000_usize {
+            offset ^= ocb.l[index.trailing_zeros() as usize];
+            assert_eq!(ocb.offset(0x42, index), offset);
+        }
+    }
+
+    #[test]
+    fn test_associated_data() {
+        let schedule = schedule();
+        let ocb = Ocb::new(&schedule);
+        let nonce = [0x01; 12];
+
+        let with_ad = ocb.encrypt(&nonce, b"header", b"payload").unwrap();
+        let without_ad = ocb.encrypt(&nonce, b"", b"payload").unwrap();
+        // Associated data changes only the tag
+        assert_eq!(with_ad[..7], without_ad[..7]);
+        assert_ne!(with_ad[7..], without_ad[7..]);
+
+        // Only associated data, nothing to encrypt
+        let tag = ocb.encrypt(&nonce, b"header", b"").unwrap();
+        assert_eq!(tag.len(), 16);
+        assert_eq!(ocb.decrypt(&nonce, b"header", &tag), Ok(vec![]));
+        assert_eq!(
+            ocb.decrypt(&nonce, b"headers", &tag),
+            Err(Error::AuthenticationFailed)
+        );
+    }
+
+    #[test]
+    fn test_tampering() {
+        let schedule = schedule();
+        let ocb = Ocb::new(&schedule);
+        let (nonce, ad, pt) = (
+            [0x01; 12],
+            b"header",
+            b"attack at dawn, not at dusk, hold until noon",
+        );
+        let ct = ocb.encrypt(&nonce, ad, pt).unwrap();
+
+        for index in 0..ct.len() {
+            let mut tampered = ct.clone();
+            tampered[index] ^= 0x01;
+            assert_eq!(
+                ocb.decrypt(&nonce, ad, &tampered),
+                Err(Error::AuthenticationFailed),
+                "byte {index}"
+            );
+        }
+        // Swapped blocks are detected as well
+        let mut swapped = ct.clone();
+        swapped[..16].copy_from_slice(&ct[16..32]);
+        swapped[16..32].copy_from_slice(&ct[..16]);
+        assert_eq!(
+            ocb.decrypt(&nonce, ad, &swapped),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(
+            ocb.decrypt(&[0x02; 12], ad, &ct),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(
+            ocb.decrypt(&nonce, ad, &ct[..15]),
+            Err(Error::WrongInputSize)
+        );
+        assert_eq!(ocb.decrypt(&nonce, ad, &ct), Ok(pt.to_vec()));
+    }
+
+    #[test]
+    fn test_parameters() {
+        let schedule = schedule();
+        let ocb = Ocb::new(&schedule);
+        assert_eq!(ocb.encrypt(&[], b"", b""), Err(Error::WrongNonceSize));
+        assert_eq!(
+            ocb.encrypt(&[0x00; 16], b"", b""),
+            Err(Error::WrongNonceSize)
+        );
+        assert_eq!(
+            ocb.decrypt(&[], b"", &[0x00; 16]),
+            Err(Error::WrongNonceSize)
+        );
+        assert!(ocb.encrypt(&[0x00; 1], b"", b"").is_ok());
+
+        assert!(matches!(
+            Ocb::with_tag_len(&schedule, 0),
+            Err(Error::WrongTagSize)
+        ));
+        assert!(matches!(
+            Ocb::with_tag_len(&schedule, 17),
+            Err(Error::WrongTagSize)
+        ));
+        let ocb = Ocb::with_tag_len(&schedule, 1).unwrap();
+        let ct = ocb.encrypt(&[0x00; 12], b"", b"data").unwrap();
+        assert_eq!(ct.len(), 5);
+        assert_eq!(ocb.decrypt(&[0x00; 12], b"", &ct), Ok(b"data".to_vec()));
+    }
+}
@@ -0,0 +1,268 @@
+use std::marker::PhantomData;
+
+use crate::{
+    aead::apply_keystream,
+    block::xor_bytes,
+    cipher::EncodeBlock,
+    mac::{cmac_subkeys, constant_time_eq, double, Cmac},
+    word::Word,
+    Error,
+};
+
+/// Synthetic IV mode from [RFC 5297](https://www.rfc-editor.org/rfc/rfc5297)
+///
+/// S2V (CMAC over every associated data component and the plaintext) gives
+/// the synthetic IV, which is both the tag and the initial counter of CTR.
+/// Encryption is deterministic: the same associated data and plaintext give
+/// the same ciphertext. A nonce, if any, goes as the last associated data
+/// component, and repeating it leaks only whether the messages are equal.
+///
+/// Takes two independent keys: one for S2V and one for CTR
+pub struct Siv<W: Word, C: EncodeBlock<W>> {
+    mac_cipher: C,
+    ctr_cipher: C,
+    /// CMAC subkeys of `mac_cipher`, derived once for all S2V calls
+    cmac_subkeys: (Vec<u8>, Vec<u8>),
+    _p: PhantomData<W>,
+}
+
+impl<W: Word, C: EncodeBlock<W>> Siv<W, C> {
+    /// SIV with `mac_cipher` for S2V and `ctr_cipher` for CTR
+    pub fn new(mac_cipher: C, ctr_cipher: C) -> Self {
+        Self {
+            cmac_subkeys: cmac_subkeys(&mac_cipher),
+            mac_cipher,
+            ctr_cipher,
+            _p: PhantomData::default(),
+        }
+    }
+
+    /// Max count of associated data components:
+    /// S2V takes one less component than the block has bits, the plaintext included
+    pub fn max_associated_data() -> usize {
+        W::BLOCK_BYTES * 8 - 2
+    }
+
+    /// Encrypt `plaintext`, authenticating every component of `associated_data`
+    ///
+    /// Returns the synthetic IV (one block) followed by the ciphertext
+    /// `Error::TooManyComponents` - if there are more than [`Siv::max_associated_data`]
+    /// associated data components
+    pub fn encrypt(&self, associated_data: &[&[u8]], plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        let iv = self.s2v(associated_data, plaintext)?;
+        let mut ciphertext = plaintext.to_vec();
+        apply_keystream(&self.ctr_cipher, &counter(&iv), &mut ciphertext);
+        Ok([iv, ciphertext].concat())
+    }
+
+    /// Decrypt the output of [`Siv::encrypt`] and check its synthetic IV
+    ///
+    /// `Error::WrongInputSize` - if `ciphertext` is shorter than a block
+    /// `Error::AuthenticationFailed` - if the synthetic IV doesn't match,
+    /// the decrypted data is dropped in that case
+    pub fn decrypt(&self, associated_data: &[&[u8]], ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        if ciphertext.len() < W::BLOCK_BYTES {
+            return Err(Error::WrongInputSize);
+        }
+        let (iv, ciphertext) = ciphertext.split_at(W::BLOCK_BYTES);
+        let mut plaintext = ciphertext.to_vec();
+        apply_keystream(&self.ctr_cipher, &counter(iv), &mut plaintext);
+
+        match constant_time_eq(&self.s2v(associated_data, &plaintext)?, iv) {
+            true => Ok(plaintext),
+            false => Err(Error::AuthenticationFailed),
+        }
+    }
+
+    /// S2V over the associated data components and the plaintext as the last one
+    fn s2v(&self, associated_data: &[&[u8]], plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        if associated_data.len() > Self::max_associated_data() {
+            return Err(Error::TooManyComponents);
+        }
+
+        let mut state = self.cmac(&[&vec![0x00; W::BLOCK_BYTES]]);
+        for component in associated_data {
+            double(&mut state);
+            xor_bytes(&mut state, &self.cmac(&[component]));
+        }
+
+        if plaintext.len() >= W::BLOCK_BYTES {
+            // XOR into the end of the plaintext, without copying it
+            let (head, tail) = plaintext.split_at(plaintext.len() - W::BLOCK_BYTES);
+            xor_bytes(&mut state, tail);
+            Ok(self.cmac(&[head, &state]))
+        } else {
+            double(&mut state);
+            xor_bytes(&mut state, plaintext);
+            state[plaintext.len()] ^= 0x80;
+            Ok(self.cmac(&[&state]))
+        }
+    }
+
+    fn cmac(&self, parts: &[&[u8]]) -> Vec<u8> {
+        let mut cmac = Cmac::with_subkeys(&self.mac_cipher, self.cmac_subkeys.clone());
+        parts.iter().for_each(|part| cmac.update(part));
+        cmac.finalize()
+    }
+}
+
+/// The synthetic IV with the top bit of the last two 32-bit words cleared,
+/// so the counter can be incremented as 32 or 64-bit integers in other implementations
+fn counter(iv: &[u8]) -> Vec<u8> {
+    let mut counter = iv.to_vec();
+    let len = counter.len();
+    if len >= 8 {
+        counter[len - 4] &= 0x7f;
+        counter[len - 8] &= 0x7f;
+    }
+    counter
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schedule::KeySchedule, settings::Rc5Settings};
+
+    fn check<W: Word>(
+        siv: &Siv<W, &KeySchedule<W>>,
+        associated_data: &[&[u8]],
+        pt_len: usize,
+        expected: &str,
+    ) {
+        let expected = hex::decode(expected).unwrap();
+        let pt = (0..pt_len as u8).collect::<Vec<u8>>();
+        assert_eq!(siv.encrypt(associated_data, &pt), Ok(expected.clone()));
+        assert_eq!(siv.decrypt(associated_data, &expected), Ok(pt));
+    }
+
+    #[test]
+    fn test_vectors() {
+        let mac_key: [u8; 16] = (0x00..0x10).collect::<Vec<_>>().try_into().unwrap();
+        let ctr_key: [u8; 16] = (0x20..0x30).collect::<Vec<_>>().try_into().unwrap();
+        let header = (0x10..0x18).collect::<Vec<u8>>();
+        let nonce = (0x30..0x3c).collect::<Vec<u8>>();
+
+        let mac_schedule = KeySchedule::new(mac_key, Rc5Settings::<u64>::new(16));
+        let ctr_schedule = KeySchedule::new(ctr_key, Rc5Settings::<u64>::new(16));
+        let siv = Siv::new(&mac_schedule, &ctr_schedule);
+        check(
+            &siv,
+            &[&header, &nonce],
+            40,
+            "c5f20dd96714c6c3e40c7ceabc2be27d2bc69360ba4cedaa2a241abd56393098\
+             a4c0ca7d22196ab56add84ec498a56ef8afa538d54c2db48",
+        );
+        check(&siv, &[], 0, "0f73862921c41c4deba395809958c034");
+        check(
+            &siv,
+            &[&header],
+            16,
+            "dcc5cec2cb41aabf5eb94c53b99415dff06e79856ce2b4b3dd7032d5c79019f1",
+        );
+
+        let mac_schedule = KeySchedule::new(mac_key, Rc5Settings::<u32>::new(12));
+        let ctr_schedule = KeySchedule::new(ctr_key, Rc5Settings::<u32>::new(12));
+        let siv = Siv::new(&mac_schedule, &ctr_schedule);
+        check(&siv, &[&header, &[], &nonce], 3, "84cc1211cb569946da94f8");
+        check(
+            &siv,
+            &[],
+            20,
+            "38ba60998240b995de5b987b9dd073ca7f6117de06a1c74f4e447499",
+        );
+
+        let mac_schedule = KeySchedule::new([0x00, 0x01, 0x02, 0x03], Rc5Settings::<u16>::new(12));
+        let ctr_schedule = KeySchedule::new([0x20, 0x21, 0x22, 0x23], Rc5Settings::<u16>::new(12));
+        let siv = Siv::new(&mac_schedule, &ctr_schedule);
+        check(&siv, &[&header[..3]], 9, "9b3bd48c2472f8a161993ef818");
+
+        let mac_schedule = KeySchedule::new([0x00, 0x01, 0x02, 0x03], Rc5Settings::<u8>::new(12));
+        let ctr_schedule = KeySchedule::new([0x20, 0x21, 0x22, 0x23], Rc5Settings::<u8>::new(12));
+        let siv = Siv::new(&mac_schedule, &ctr_schedule);
+        check(&siv, &[&header[..2], &nonce[..1]], 5, "33dce489c7d42a");
+
+        let mac_schedule = KeySchedule::new(mac_key, Rc5Settings::<u128>::new(20));
+        let ctr_schedule = KeySchedule::new(ctr_key, Rc5Settings::<u128>::new(20));
+        let siv = Siv::new(&mac_schedule, &ctr_schedule);
+        check(
+            &siv,
+            &[&header, &nonce],
+            50,
+            "baaa89d5498a686fce2e21cc8af16d67ed098fdf552b3e0fb4e952b2c6c31186\
+             b7c949cc1521fa46e71f54e755871016078ea043b9a3e2b6249bf6069a2f7538\
+             0631ab0cb19ff1d3680330093fbae3a83aaf",
+        );
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let mac_schedule = KeySchedule::new([0x01; 16], Rc5Settings::<u64>::new(16));
+        let ctr_schedule = KeySchedule::new([0x02; 16], Rc5Settings::<u64>::new(16));
+        let siv = Siv::new(&mac_schedule, &ctr_schedule);
+
+        let first = siv.encrypt(&[b"file", b"nonce"], b"same content").unwrap();
+        assert_eq!(
+            siv.encrypt(&[b"file", b"nonce"], b"same content"),
+            Ok(first.clone())
+        );
+        // With a reused nonce the only visible thing is the (in)equality of messages
+        let other = siv.encrypt(&[b"file", b"nonce"], b"some content").unwrap();
+        assert_ne!(first[..16], other[..16]);
+        assert_ne!(first[16..], other[16..]);
+
+        // Components are separated, not concatenated
+        assert_ne!(
+            siv.encrypt(&[b"filenonce"], b"same content"),
+            Ok(first.clone())
+        );
+        assert_ne!(
+            siv.encrypt(&[b"file", b"", b"nonce"], b"same content"),
+            Ok(first.clone())
+        );
+        assert_ne!(
+            siv.encrypt(&[b"nonce", b"file"], b"same content"),
+            Ok(first)
+        );
+    }
+
+    #[test]
+    fn test_tampering() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::default());
+        let siv = Siv::new(&schedule, &schedule);
+        let ad: [&[u8]; 2] = [b"header", b"nonce"];
+        let ct = siv.encrypt(&ad, b"attack at dawn").unwrap();
+
+        for index in 0..ct.len() {
+            let mut tampered = ct.clone();
+            tampered[index] ^= 0x01;
+            assert_eq!(
+                siv.decrypt(&ad, &tampered),
+                Err(Error::AuthenticationFailed),
+                "byte {index}"
+            );
+        }
+        assert_eq!(
+            siv.decrypt(&[b"header"], &ct),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(
+            siv.decrypt(&[b"header", b"nonce", b""], &ct),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(siv.decrypt(&ad, &ct[..7]), Err(Error::WrongInputSize));
+        assert_eq!(siv.decrypt(&ad, &ct), Ok(b"attack at dawn".to_vec()));
+    }
+
+    #[test]
+    fn test_too_many_components() {
+        let schedule = KeySchedule::new([0x42; 4], Rc5Settings::<u8>::new(12));
+        let siv = Siv::new(&schedule, &schedule);
+        assert_eq!(Siv::<u8, &KeySchedule<u8>>::max_associated_data(), 14);
+
+        let ad = [&b"x"[..]; 15];
+        assert_eq!(siv.encrypt(&ad, b"pt"), Err(Error::TooManyComponents));
+        assert_eq!(siv.decrypt(&ad, &[0x00; 4]), Err(Error::TooManyComponents));
+        let ct = siv.encrypt(&ad[..14], b"pt").unwrap();
+        assert_eq!(siv.decrypt(&ad[..14], &ct), Ok(b"pt".to_vec()));
+    }
+}
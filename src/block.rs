@@ -1,8 +1,11 @@
+use std::marker::PhantomData;
+
 use itertools::Itertools;
 
 use crate::{
     key::{Key, MixinKey},
-    word::{RotateWordLeft, RotateWordRight, Word},
+    trace::{NoTrace, Observer, Register, TraceEvent},
+    word::{RotateWordLeft, RotateWordRight, RotationAmount, Word},
 };
 
 /// Unfortunately, constant calculations in Rust
@@ -16,28 +19,66 @@ pub enum Error {
 }
 
 pub(crate) trait DecodeAsBlocks {
-    /// This function splits `Self` into blocks (pair of words) and executes the RC5 encryption algorithm
+    /// This function splits `Self` into blocks (pair of words) and executes the RC5 decryption algorithm
     /// `Error` - if `&self` cannot be divided into blocks!
     fn decode_as_blocks<W: Word>(&self, key: impl Key, round_count: u8) -> Result<Vec<u8>, Error>;
+
+    /// Same as [`DecodeAsBlocks::decode_as_blocks`], but reports every step to `observer`
+    fn decode_as_blocks_observed<W: Word>(
+        &self,
+        key: impl Key,
+        round_count: u8,
+        observer: &mut impl Observer<W>,
+    ) -> Result<Vec<u8>, Error>;
 }
 impl<T: AsRef<[u8]>> DecodeAsBlocks for T {
     fn decode_as_blocks<W: Word>(&self, key: impl Key, round_count: u8) -> Result<Vec<u8>, Error> {
+        let key_table = key.mixin::<W>(round_count);
+        process_blocks(self.as_ref(), |b| rc5_decode(b, &key_table, round_count))
+    }
+
+    fn decode_as_blocks_observed<W: Word>(
+        &self,
+        key: impl Key,
+        round_count: u8,
+        observer: &mut impl Observer<W>,
+    ) -> Result<Vec<u8>, Error> {
+        let key_table = key.mixin::<W>(round_count);
         process_blocks(self.as_ref(), |b| {
-            rc5_decode(b, &key.mixin::<W>(round_count), round_count)
+            rc5_decode_observed(b, &key_table, round_count, observer)
         })
     }
 }
 
 pub(crate) trait EncodeAsBlocks {
-    /// This function splits `Self` into blocks (pair of words) and executes the RC5 decryption algorithm
+    /// This function splits `Self` into blocks (pair of words) and executes the RC5 encryption algorithm
     /// `Error` - if `&self` cannot be divided into blocks!
     fn encode_as_blocks<W: Word>(&self, key: impl Key, round_count: u8) -> Result<Vec<u8>, Error>;
+
+    /// Same as [`EncodeAsBlocks::encode_as_blocks`], but reports every step to `observer`
+    fn encode_as_blocks_observed<W: Word>(
+        &self,
+        key: impl Key,
+        round_count: u8,
+        observer: &mut impl Observer<W>,
+    ) -> Result<Vec<u8>, Error>;
 }
 
 impl<T: AsRef<[u8]>> EncodeAsBlocks for T {
     fn encode_as_blocks<W: Word>(&self, key: impl Key, round_count: u8) -> Result<Vec<u8>, Error> {
+        let key_table = key.mixin::<W>(round_count);
+        process_blocks(self.as_ref(), |b| rc5_encode(b, &key_table, round_count))
+    }
+
+    fn encode_as_blocks_observed<W: Word>(
+        &self,
+        key: impl Key,
+        round_count: u8,
+        observer: &mut impl Observer<W>,
+    ) -> Result<Vec<u8>, Error> {
+        let key_table = key.mixin::<W>(round_count);
         process_blocks(self.as_ref(), |b| {
-            rc5_encode(b, &key.mixin::<W>(round_count), round_count)
+            rc5_encode_observed(b, &key_table, round_count, observer)
         })
     }
 }
@@ -47,9 +88,9 @@ impl<T: AsRef<[u8]>> EncodeAsBlocks for T {
 /// each `processor` closure
 ///
 /// `Error` - cannot be divided into blocks!
-fn process_blocks<W: Word>(
+pub(crate) fn process_blocks<W: Word>(
     input: &[u8],
-    processor: impl Fn((W, W)) -> (W, W),
+    mut processor: impl FnMut((W, W)) -> (W, W),
 ) -> Result<Vec<u8>, Error> {
     if input.len() % W::BYTES != 0 {
         return Err(Error::WrongInputSize);
@@ -72,43 +113,159 @@ fn process_blocks<W: Word>(
         })
 }
 
+/// Compile-time check that an array of `N` bytes can be divided into blocks of `W`
+pub(crate) struct BlockMultiple<W: Word, const N: usize>(PhantomData<W>);
+impl<W: Word, const N: usize> BlockMultiple<W, N> {
+    pub(crate) const CHECK: () = assert!(
+        N % W::BLOCK_BYTES == 0,
+        "The array length must be a multiple of the block bytes len"
+    );
+}
+
+/// Load a block from the first `W::BLOCK_BYTES` bytes of `bytes`
+pub(crate) fn read_block<W: Word>(bytes: &[u8]) -> (W, W) {
+    (
+        W::from_le_bytes(&bytes[..W::BYTES]),
+        W::from_le_bytes(&bytes[W::BYTES..W::BLOCK_BYTES]),
+    )
+}
+
+/// Store a block into the first `W::BLOCK_BYTES` bytes of `bytes`
+pub(crate) fn write_block<W: Word>(block: (W, W), bytes: &mut [u8]) {
+    bytes[..W::BYTES].copy_from_slice(&block.0.into_le_bytes());
+    bytes[W::BYTES..W::BLOCK_BYTES].copy_from_slice(&block.1.into_le_bytes());
+}
+
+/// Append a block to `output` as bytes
+pub(crate) fn append_block<W: Word>(output: &mut Vec<u8>, block: (W, W)) {
+    output.append(&mut block.0.into_le_bytes());
+    output.append(&mut block.1.into_le_bytes());
+}
+
+/// Word-wise XOR of two blocks, the same as XOR of their bytes
+pub(crate) fn xor_block<W: Word>(lhs: (W, W), rhs: (W, W)) -> (W, W) {
+    (lhs.0.bitxor(rhs.0), lhs.1.bitxor(rhs.1))
+}
+
+/// XOR `rhs` into `lhs` byte by byte, up to the shorter of them
+pub(crate) fn xor_bytes(lhs: &mut [u8], rhs: &[u8]) {
+    lhs.iter_mut().zip(rhs).for_each(|(lhs, rhs)| *lhs ^= rhs);
+}
+
+/// Same as [`process_blocks`], but the array size
+/// is checked at compile time, so it can't fail
+pub(crate) fn process_array<W: Word, const N: usize>(
+    mut input: [u8; N],
+    mut processor: impl FnMut((W, W)) -> (W, W),
+) -> [u8; N] {
+    // The binding forces the evaluation, so a wrong `N` fails the build
+    #[allow(clippy::let_unit_value)]
+    let () = BlockMultiple::<W, N>::CHECK;
+
+    for chunk in input.chunks_exact_mut(W::BLOCK_BYTES) {
+        write_block(processor(read_block(chunk)), chunk);
+    }
+    input
+}
+
 /// RC5 Encode Function
 /// Check 4.1 in [the specification](https://www.grc.com/r&d/rc5.pdf).
-fn rc5_encode<W: Word>(block: (W, W), key_table: &[W], round_count: u8) -> (W, W) {
+pub(crate) fn rc5_encode<W: Word>(block: (W, W), key_table: &[W], round_count: u8) -> (W, W) {
+    rc5_encode_observed(block, key_table, round_count, &mut NoTrace)
+}
+
+/// RC5 Encode Function that reports every step to `observer`
+/// Check 4.1 in [the specification](https://www.grc.com/r&d/rc5.pdf).
+fn rc5_encode_observed<W: Word>(
+    block: (W, W),
+    key_table: &[W],
+    round_count: u8,
+    observer: &mut impl Observer<W>,
+) -> (W, W) {
     let (mut a, mut b) = block;
+    observer.observe(TraceEvent::Input { a, b });
 
     a = a.wrapping_add(&key_table[0]);
     b = b.wrapping_add(&key_table[1]);
+    observer.observe(TraceEvent::Whitening { a, b });
+
     for index in 1..=(round_count as usize) {
+        let rotation = b.rotation_amount();
         a = a
             .bitxor(b)
             .rotate_word_left(b)
             .wrapping_add(&key_table[2 * index]);
+        observer.observe(TraceEvent::HalfRound {
+            round: index as u8,
+            register: Register::A,
+            a,
+            b,
+            rotation,
+        });
+
+        let rotation = a.rotation_amount();
         b = b
             .bitxor(a)
             .rotate_word_left(a)
             .wrapping_add(&key_table[2 * index + 1]);
+        observer.observe(TraceEvent::HalfRound {
+            round: index as u8,
+            register: Register::B,
+            a,
+            b,
+            rotation,
+        });
     }
     (a, b)
 }
 
 /// RC5 Decode Function
 /// Check 4.2 in [the specification](https://www.grc.com/r&d/rc5.pdf).
-fn rc5_decode<W: Word>(block: (W, W), key_table: &[W], round_count: u8) -> (W, W) {
+pub(crate) fn rc5_decode<W: Word>(block: (W, W), key_table: &[W], round_count: u8) -> (W, W) {
+    rc5_decode_observed(block, key_table, round_count, &mut NoTrace)
+}
+
+/// RC5 Decode Function that reports every step to `observer`
+/// Check 4.2 in [the specification](https://www.grc.com/r&d/rc5.pdf).
+fn rc5_decode_observed<W: Word>(
+    block: (W, W),
+    key_table: &[W],
+    round_count: u8,
+    observer: &mut impl Observer<W>,
+) -> (W, W) {
     let (mut a, mut b) = block;
+    observer.observe(TraceEvent::Input { a, b });
 
     for index in (1..=round_count as usize).rev() {
+        let rotation = a.rotation_amount();
         b = b
             .wrapping_sub(&key_table[2 * index + 1])
             .rotate_word_right(a)
             .bitxor(a);
+        observer.observe(TraceEvent::HalfRound {
+            round: index as u8,
+            register: Register::B,
+            a,
+            b,
+            rotation,
+        });
+
+        let rotation = b.rotation_amount();
         a = a
             .wrapping_sub(&key_table[2 * index])
             .rotate_word_right(b)
             .bitxor(b);
+        observer.observe(TraceEvent::HalfRound {
+            round: index as u8,
+            register: Register::A,
+            a,
+            b,
+            rotation,
+        });
     }
     b = b.wrapping_sub(&key_table[1]);
     a = a.wrapping_sub(&key_table[0]);
+    observer.observe(TraceEvent::Whitening { a, b });
 
     (a, b)
 }
@@ -133,6 +290,81 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_encode_observed() {
+        let mut events = vec![];
+        assert_eq!(
+            rc5_encode_observed((10u16, 10u16), &[0x00, 0x01, 0x02, 0x03], 1, &mut |event| {
+                events.push(event)
+            }),
+            (2050, 8231)
+        );
+        assert_eq!(
+            events,
+            [
+                TraceEvent::Input { a: 10, b: 10 },
+                TraceEvent::Whitening { a: 10, b: 11 },
+                TraceEvent::HalfRound {
+                    round: 1,
+                    register: Register::A,
+                    a: 2050,
+                    b: 11,
+                    rotation: 11
+                },
+                TraceEvent::HalfRound {
+                    round: 1,
+                    register: Register::B,
+                    a: 2050,
+                    b: 8231,
+                    rotation: 2
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_observed_mirrors_encode() {
+        let key_table = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05];
+        let mut encode_events = vec![];
+        let mut decode_events = vec![];
+        let ct = rc5_encode_observed((10u16, 10u16), &key_table, 2, &mut |event| {
+            encode_events.push(event)
+        });
+        rc5_decode_observed(ct, &key_table, 2, &mut |event| decode_events.push(event));
+
+        // Decryption must pass the same states as encryption, in reverse order
+        let states = |events: &[TraceEvent<u16>]| {
+            events
+                .iter()
+                .map(|event| match *event {
+                    TraceEvent::Input { a, b }
+                    | TraceEvent::Whitening { a, b }
+                    | TraceEvent::HalfRound { a, b, .. } => (a, b),
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut decode_states = states(&decode_events);
+        decode_states.reverse();
+        assert_eq!(decode_states, states(&encode_events));
+    }
+
+    #[test]
+    fn test_process_array() {
+        assert_eq!(
+            process_array([0xff, 0xf0, 0xff, 0xf0], |(w1, w2): (u8, u8)| -> (u8, u8) {
+                (w2, w1)
+            }),
+            [0xf0, 0xff, 0xf0, 0xff]
+        );
+        assert_eq!(
+            process_array(
+                [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08],
+                |(w1, w2): (u32, u32)| { (w2, w1) }
+            ),
+            [0x05, 0x06, 0x07, 0x08, 0x01, 0x02, 0x03, 0x04]
+        );
+    }
+
     #[test]
     fn test_process_blocks() {
         assert_eq!(
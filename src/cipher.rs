@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use crate::{
+    block::{process_array, process_blocks},
+    key::Key,
+    schedule::KeySchedule,
+    settings::Rc5Settings,
+    word::Word,
+    Error,
+};
+
+/// Keyed RC5 cipher that is able to encode
+pub trait EncodeBlock<W: Word> {
+    /// Encode a single block (pair of words)
+    ///
+    /// Check 4.1 in [the specification](https://www.grc.com/r&d/rc5.pdf).
+    fn encode_block(&self, block: (W, W)) -> (W, W);
+
+    /// Same as [`crate::EncodeRc5`], but with already expanded key
+    /// `Error` - if `input` cannot be divided into blocks!
+    fn encode(&self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(process_blocks(input, |b| self.encode_block(b))?)
+    }
+
+    /// Same as [`EncodeBlock::encode`], but `N` is checked
+    /// to be a multiple of the block size at compile time
+    fn encode_array<const N: usize>(&self, input: [u8; N]) -> [u8; N] {
+        process_array(input, |b| self.encode_block(b))
+    }
+}
+
+/// Keyed RC5 cipher that is able to decode
+pub trait DecodeBlock<W: Word> {
+    /// Decode a single block (pair of words)
+    ///
+    /// Check 4.2 in [the specification](https://www.grc.com/r&d/rc5.pdf).
+    fn decode_block(&self, block: (W, W)) -> (W, W);
+
+    /// Same as [`crate::DecodeRc5`], but with already expanded key
+    /// `Error` - if `input` cannot be divided into blocks!
+    fn decode(&self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(process_blocks(input, |b| self.decode_block(b))?)
+    }
+
+    /// Same as [`DecodeBlock::decode`], but `N` is checked
+    /// to be a multiple of the block size at compile time
+    fn decode_array<const N: usize>(&self, input: [u8; N]) -> [u8; N] {
+        process_array(input, |b| self.decode_block(b))
+    }
+}
+
+impl<W: Word, T: EncodeBlock<W> + ?Sized> EncodeBlock<W> for &T {
+    fn encode_block(&self, block: (W, W)) -> (W, W) {
+        (**self).encode_block(block)
+    }
+}
+
+impl<W: Word, T: DecodeBlock<W> + ?Sized> DecodeBlock<W> for &T {
+    fn decode_block(&self, block: (W, W)) -> (W, W) {
+        (**self).decode_block(block)
+    }
+}
+
+/// Handle that can only encode under a key
+///
+/// Unlike [`KeySchedule`] it doesn't implement [`DecodeBlock`] and doesn't give
+/// access to the schedule, so it can't be turned into [`Rc5Decryptor`]:
+/// ```compile_fail
+/// use rc5_cypher::*;
+///
+/// let encryptor = Rc5Encryptor::from_key([0x00; 16], Rc5Settings::default());
+/// encryptor.decode(&[0x00; 8]);
+/// ```
+/// The schedule is shared, so building both handles from the same
+/// `Arc<KeySchedule<W>>` doesn't copy the table
+pub struct Rc5Encryptor<W: Word> {
+    schedule: Arc<KeySchedule<W>>,
+}
+
+impl<W: Word> Rc5Encryptor<W> {
+    pub fn new(schedule: impl Into<Arc<KeySchedule<W>>>) -> Self {
+        Self {
+            schedule: schedule.into(),
+        }
+    }
+
+    pub fn from_key(key: impl Key, settings: Rc5Settings<W>) -> Self {
+        Self::new(KeySchedule::new(key, settings))
+    }
+}
+
+impl<W: Word> EncodeBlock<W> for Rc5Encryptor<W> {
+    fn encode_block(&self, block: (W, W)) -> (W, W) {
+        self.schedule.encode_block(block)
+    }
+}
+
+/// Handle that can only decode under a key
+///
+/// Unlike [`KeySchedule`] it doesn't implement [`EncodeBlock`] and doesn't give
+/// access to the schedule, so it can't be turned into [`Rc5Encryptor`]:
+/// ```compile_fail
+/// use rc5_cypher::*;
+///
+/// let decryptor = Rc5Decryptor::from_key([0x00; 16], Rc5Settings::default());
+/// decryptor.encode(&[0x00; 8]);
+/// ```
+/// The schedule is shared, so building both handles from the same
+/// `Arc<KeySchedule<W>>` doesn't copy the table
+pub struct Rc5Decryptor<W: Word> {
+    schedule: Arc<KeySchedule<W>>,
+}
+
+impl<W: Word> Rc5Decryptor<W> {
+    pub fn new(schedule: impl Into<Arc<KeySchedule<W>>>) -> Self {
+        Self {
+            schedule: schedule.into(),
+        }
+    }
+
+    pub fn from_key(key: impl Key, settings: Rc5Settings<W>) -> Self {
+        Self::new(KeySchedule::new(key, settings))
+    }
+}
+
+impl<W: Word> DecodeBlock<W> for Rc5Decryptor<W> {
+    fn decode_block(&self, block: (W, W)) -> (W, W) {
+        self.schedule.decode_block(block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_schedule() {
+        let key = [
+            0x2B, 0xD6, 0x45, 0x9F, 0x82, 0xC5, 0xB3, 0x00, 0x95, 0x2C, 0x49, 0x10, 0x48, 0x81,
+            0xFF, 0x48,
+        ];
+        let pt = [0xEA, 0x02, 0x47, 0x14, 0xAD, 0x5C, 0x4D, 0x84];
+        let ct = [0x11, 0xE4, 0x3B, 0x86, 0xD2, 0x31, 0xEA, 0x64];
+
+        let schedule = Arc::new(KeySchedule::new(key, Rc5Settings::default()));
+        let encryptor = Rc5Encryptor::new(schedule.clone());
+        let decryptor = Rc5Decryptor::new(schedule);
+
+        assert_eq!(encryptor.encode(&pt).unwrap(), ct);
+        assert_eq!(decryptor.decode(&ct).unwrap(), pt);
+        assert_eq!(encryptor.encode_array(pt), ct);
+        assert_eq!(decryptor.decode_array(ct), pt);
+    }
+
+    #[test]
+    fn test_from_key() {
+        let key = [0x00, 0x01, 0x02, 0x03];
+        assert_eq!(
+            Rc5Encryptor::from_key(key, Rc5Settings::<u8>::new(12))
+                .encode(&[0x00, 0x01])
+                .unwrap(),
+            [0x21, 0x2a]
+        );
+        assert_eq!(
+            Rc5Decryptor::from_key(key, Rc5Settings::<u8>::new(12))
+                .decode(&[0x21, 0x2a])
+                .unwrap(),
+            [0x00, 0x01]
+        );
+        assert_eq!(
+            Rc5Decryptor::from_key(key, Rc5Settings::<u8>::new(12)).decode(&[0x21]),
+            Err(Error::WrongInputSize)
+        );
+    }
+}
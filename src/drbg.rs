@@ -0,0 +1,384 @@
+use crate::{
+    block::xor_bytes, mac::encode_bytes, schedule::KeySchedule, settings::Rc5Settings, word::Word,
+    Error,
+};
+
+/// RC5 key length of the generator: RC5-w/r/128
+const KEY_LEN: usize = 16;
+
+/// Bytes generated at once for the small requests of `RngCore`
+#[cfg(feature = "rand_core")]
+const RNG_BUFFER_LEN: usize = 256;
+
+/// Deterministic random bit generator CTR_DRBG from
+/// [NIST SP 800-90A](https://doi.org/10.6028/NIST.SP.800-90Ar1), built on RC5
+///
+/// The key is 16 bytes, the counter `V` is one block of the chosen [`Word`].
+/// Seed material goes through the block cipher derivation function, so the entropy
+/// input, the nonce and the personalization string can be of any length.
+/// The output depends only on the inputs and the word, the same on every platform.
+///
+/// With the `rand_core` feature it implements `RngCore` and `SeedableRng`.
+/// `RngCore` serves requests shorter than 256 bytes from a buffer, refilled by one
+/// [`CtrDrbg::generate`] call. Its `next_u32`, `next_u64` and `fill_bytes` panic
+/// once a reseed is required, `try_fill_bytes` returns the error instead
+pub struct CtrDrbg<W: Word> {
+    rounds_count: u8,
+    key: [u8; KEY_LEN],
+    schedule: KeySchedule<W>,
+    v: Vec<u8>,
+    reseed_counter: u64,
+    reseed_interval: u64,
+    /// Generated bytes not yet returned by `RngCore`
+    #[cfg(feature = "rand_core")]
+    buffer: Vec<u8>,
+}
+
+impl<W: Word> CtrDrbg<W> {
+    /// Min length of the entropy input, the security strength of the generator
+    pub const MIN_ENTROPY_LEN: usize = 16;
+    /// Max bytes returned by one [`CtrDrbg::generate`] call
+    pub const MAX_REQUEST_LEN: usize = 1 << 16;
+    /// Default count of [`CtrDrbg::generate`] calls before a reseed is required
+    pub const RESEED_INTERVAL: u64 = 1 << 48;
+
+    /// Instantiate the generator
+    ///
+    /// `Error::WrongSeedSize` - if `entropy` is shorter than [`CtrDrbg::MIN_ENTROPY_LEN`]
+    pub fn new(
+        settings: Rc5Settings<W>,
+        entropy: &[u8],
+        nonce: &[u8],
+        personalization: &[u8],
+    ) -> Result<Self, Error> {
+        if entropy.len() < Self::MIN_ENTROPY_LEN {
+            return Err(Error::WrongSeedSize);
+        }
+        let key = [0x00; KEY_LEN];
+        let mut drbg = Self {
+            rounds_count: settings.rounds_count,
+            key,
+            schedule: KeySchedule::new(key, settings),
+            v: vec![0x00; W::BLOCK_BYTES],
+            reseed_counter: 1,
+            reseed_interval: Self::RESEED_INTERVAL,
+            #[cfg(feature = "rand_core")]
+            buffer: Vec::with_capacity(RNG_BUFFER_LEN),
+        };
+        let seed_material = drbg.derive(&[entropy, nonce, personalization].concat());
+        drbg.update(&seed_material);
+        Ok(drbg)
+    }
+
+    /// Require a reseed after `reseed_interval` calls of [`CtrDrbg::generate`]
+    /// instead of [`CtrDrbg::RESEED_INTERVAL`]
+    pub fn with_reseed_interval(mut self, reseed_interval: u64) -> Self {
+        self.reseed_interval = reseed_interval;
+        self
+    }
+
+    /// Mix fresh `entropy` and optional `additional_input` into the state
+    /// and reset the reseed counter. Bytes buffered for `RngCore` are dropped
+    ///
+    /// `Error::WrongSeedSize` - if `entropy` is shorter than [`CtrDrbg::MIN_ENTROPY_LEN`]
+    pub fn reseed(&mut self, entropy: &[u8], additional_input: &[u8]) -> Result<(), Error> {
+        if entropy.len() < Self::MIN_ENTROPY_LEN {
+            return Err(Error::WrongSeedSize);
+        }
+        let seed_material = self.derive(&[entropy, additional_input].concat());
+        self.update(&seed_material);
+        self.reseed_counter = 1;
+        #[cfg(feature = "rand_core")]
+        self.buffer.clear();
+        Ok(())
+    }
+
+    /// Fill `output` with random bytes, optionally mixing `additional_input` into the state
+    ///
+    /// `Error::ReseedRequired` - if the reseed interval has passed
+    /// `Error::WrongInputSize` - if `output` is longer than [`CtrDrbg::MAX_REQUEST_LEN`]
+    pub fn generate(&mut self, output: &mut [u8], additional_input: &[u8]) -> Result<(), Error> {
+        if self.reseed_counter > self.reseed_interval {
+            return Err(Error::ReseedRequired);
+        }
+        if output.len() > Self::MAX_REQUEST_LEN {
+            return Err(Error::WrongInputSize);
+        }
+
+        let additional_input = match additional_input.is_empty() {
+            true => vec![0x00; KEY_LEN + W::BLOCK_BYTES],
+            false => {
+                let additional_input = self.derive(additional_input);
+                self.update(&additional_input);
+                additional_input
+            }
+        };
+        for chunk in output.chunks_mut(W::BLOCK_BYTES) {
+            let block = self.next_block();
+            chunk.copy_from_slice(&block[..chunk.len()]);
+        }
+        self.update(&additional_input);
+        self.reseed_counter += 1;
+        Ok(())
+    }
+
+    /// Count of [`CtrDrbg::generate`] calls since the last (re)seed, plus one
+    pub fn reseed_counter(&self) -> u64 {
+        self.reseed_counter
+    }
+
+    /// Increment `V` as a big-endian number and encode it
+    fn next_block(&mut self) -> Vec<u8> {
+        for byte in self.v.iter_mut().rev() {
+            *byte = byte.wrapping_add(1);
+            if *byte != 0x00 {
+                break;
+            }
+        }
+        let mut block = self.v.clone();
+        encode_bytes(&self.schedule, &mut block);
+        block
+    }
+
+    /// `CTR_DRBG_Update`: a new key and `V` from the keystream XOR `provided_data`
+    fn update(&mut self, provided_data: &[u8]) {
+        let mut temp = Vec::with_capacity(KEY_LEN + 2 * W::BLOCK_BYTES);
+        while temp.len() < KEY_LEN + W::BLOCK_BYTES {
+            temp.extend(self.next_block());
+        }
+        temp.truncate(KEY_LEN + W::BLOCK_BYTES);
+        xor_bytes(&mut temp, provided_data);
+
+        self.key.copy_from_slice(&temp[..KEY_LEN]);
+        self.schedule = KeySchedule::new(self.key, Rc5Settings::new(self.rounds_count));
+        self.v.copy_from_slice(&temp[KEY_LEN..]);
+    }
+
+    /// `Block_Cipher_df`: compress `input` of any length into seed length bytes
+    fn derive(&self, input: &[u8]) -> Vec<u8> {
+        let seed_len = KEY_LEN + W::BLOCK_BYTES;
+        let mut s = Vec::with_capacity(input.len() + 9 + W::BLOCK_BYTES);
+        s.extend((input.len() as u32).to_be_bytes());
+        s.extend((seed_len as u32).to_be_bytes());
+        s.extend_from_slice(input);
+        s.push(0x80);
+        s.resize(
+            s.len() + (W::BLOCK_BYTES - s.len() % W::BLOCK_BYTES) % W::BLOCK_BYTES,
+            0x00,
+        );
+
+        let key: [u8; KEY_LEN] = std::array::from_fn(|index| index as u8);
+        let schedule = KeySchedule::new(key, Rc5Settings::<W>::new(self.rounds_count));
+        let mut temp = Vec::with_capacity(seed_len + W::BLOCK_BYTES);
+        let mut index = 0u32;
+        while temp.len() < seed_len {
+            // The index takes the first 4 bytes of the block, or its last bytes for short blocks
+            let mut iv = index.to_be_bytes().to_vec();
+            iv.resize(W::BLOCK_BYTES.max(4), 0x00);
+            temp.extend(bcc(&schedule, &iv[iv.len() - W::BLOCK_BYTES..], &s));
+            index += 1;
+        }
+
+        let key: [u8; KEY_LEN] = temp[..KEY_LEN].try_into().unwrap();
+        let schedule = KeySchedule::new(key, Rc5Settings::<W>::new(self.rounds_count));
+        let mut block = temp[KEY_LEN..seed_len].to_vec();
+        let mut output = Vec::with_capacity(seed_len + W::BLOCK_BYTES);
+        while output.len() < seed_len {
+            encode_bytes(&schedule, &mut block);
+            output.extend_from_slice(&block);
+        }
+        output.truncate(seed_len);
+        output
+    }
+}
+
+/// `BCC`: CBC-MAC of `iv || data` with the zero IV
+fn bcc<W: Word>(schedule: &KeySchedule<W>, iv: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut chaining = vec![0x00; W::BLOCK_BYTES];
+    for block in [iv].into_iter().chain(data.chunks(W::BLOCK_BYTES)) {
+        xor_bytes(&mut chaining, block);
+        encode_bytes(schedule, &mut chaining);
+    }
+    chaining
+}
+
+#[cfg(feature = "rand_core")]
+impl<W: Word> rand_core::RngCore for CtrDrbg<W> {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    /// Panics if a reseed is required, use `try_fill_bytes` to handle it
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest)
+            .expect("The generator must be reseeded")
+    }
+
+    /// `Error::ReseedRequired` as `rand_core::Error` - if the reseed interval has passed
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        let buffered = self.buffer.len().min(dest.len());
+        let (head, dest) = dest.split_at_mut(buffered);
+        head.copy_from_slice(&self.buffer[..buffered]);
+        self.buffer.drain(..buffered);
+        if dest.is_empty() {
+            return Ok(());
+        }
+
+        if dest.len() < RNG_BUFFER_LEN {
+            let mut buffer = vec![0x00; RNG_BUFFER_LEN];
+            self.generate(&mut buffer, &[])
+                .map_err(rand_core::Error::new)?;
+            dest.copy_from_slice(&buffer[..dest.len()]);
+            buffer.drain(..dest.len());
+            self.buffer = buffer;
+            return Ok(());
+        }
+        dest.chunks_mut(Self::MAX_REQUEST_LEN)
+            .try_for_each(|chunk| self.generate(chunk, &[]))
+            .map_err(rand_core::Error::new)
+    }
+}
+
+/// The seed is the entropy input of RC5-w/12/128 generator,
+/// without a nonce and a personalization string
+#[cfg(feature = "rand_core")]
+impl<W: Word> rand_core::SeedableRng for CtrDrbg<W> {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self::new(Rc5Settings::new(12), &seed, &[], &[])
+            .expect("The seed is longer than the min entropy")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(range: std::ops::Range<u8>) -> Vec<u8> {
+        range.collect()
+    }
+
+    fn generate<W: Word>(drbg: &mut CtrDrbg<W>, len: usize, additional_input: &[u8]) -> String {
+        let mut output = vec![0x00; len];
+        drbg.generate(&mut output, additional_input).unwrap();
+        hex::encode(output)
+    }
+
+    fn check<W: Word>(first: &str, second: &str) {
+        let mut drbg = CtrDrbg::<W>::new(
+            Rc5Settings::new(12),
+            &bytes(0x00..0x20),
+            &bytes(0x20..0x28),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(generate(&mut drbg, 40, &[]), first);
+        assert_eq!(generate(&mut drbg, 40, &[]), second);
+    }
+
+    #[test]
+    fn test_vectors() {
+        check::<u8>(
+            "312f4c5325b44d15dbbb246c8dfb747b5a1fdbd2a91cdf8f92657829577bad8d1a8b3695703c8ced",
+            "85cde0ed982d5e67afea072292663e0a44d6a5225d99a87d584e8460b1751a491560f1af8aadf9c4",
+        );
+        check::<u16>(
+            "d8b84ff5ccf2d9a57962c57a4f4493847b75aa8822286353760bfea2a48bbebe5d0c7b886b180e30",
+            "4a1785d55beaab2de72d5094a4b9160923d2761dd05c84403891b06eb7daed5532a50d79be1b773f",
+        );
+        check::<u32>(
+            "d1704fe8028d4291617992cbe139bbc20ad909e05866958caf61324df82ec01b908765309c187a4c",
+            "5f2c1e1fe554d046783b15300346b4dcfb354080c72bdf8243dc7a2f3b2f6c775aec6f057f82cede",
+        );
+        check::<u64>(
+            "70edf6e5fdf3a6fe8246c747a47fc4e97b96506f61a0e668bc4c35e2a2e9bea927709998f0233ce5",
+            "b04aab7347eef260f6e3b0b938b71c3243fbbd736c58bc6c9051dc6ecf3832ead5c51c983976b9d8",
+        );
+        check::<u128>(
+            "9ef7d6e733212ac29ccf525fabc8d045d86330732c36c2fdd820b200ef0f469b9ff5f810010bb536",
+            "9ecc94918a12b9a74778fc8051cc52cce76be7a3b7ec2268d6b95ea1285f254e24a96262bd092361",
+        );
+    }
+
+    #[test]
+    fn test_reseed() {
+        let mut drbg = CtrDrbg::<u32>::new(
+            Rc5Settings::new(12),
+            &bytes(0x00..0x20),
+            &bytes(0x20..0x28),
+            b"personalization",
+        )
+        .unwrap();
+        assert_eq!(
+            generate(&mut drbg, 24, b"additional"),
+            "2c28dde32bd4c2aea32ccd78420afeeda7b3eadf09d34910"
+        );
+        assert_eq!(drbg.reseed_counter(), 2);
+
+        drbg.reseed(&bytes(0x40..0x60), b"reseed").unwrap();
+        assert_eq!(drbg.reseed_counter(), 1);
+        assert_eq!(
+            generate(&mut drbg, 24, &[]),
+            "6f06a664567e6cacb4db656bf216f36874c8a2f811b1dbf8"
+        );
+
+        assert_eq!(drbg.reseed(&[0x00; 15], &[]), Err(Error::WrongSeedSize));
+    }
+
+    #[test]
+    fn test_limits() {
+        assert!(matches!(
+            CtrDrbg::<u32>::new(Rc5Settings::new(12), &[0x00; 15], &[], &[]),
+            Err(Error::WrongSeedSize)
+        ));
+
+        let mut drbg = CtrDrbg::<u32>::new(Rc5Settings::new(12), &[0x42; 16], &[], &[])
+            .unwrap()
+            .with_reseed_interval(2);
+        let mut output = vec![0x00; CtrDrbg::<u32>::MAX_REQUEST_LEN + 1];
+        assert_eq!(drbg.generate(&mut output, &[]), Err(Error::WrongInputSize));
+        assert_eq!(drbg.generate(&mut output[1..], &[]), Ok(()));
+        assert_eq!(drbg.generate(&mut [], &[]), Ok(()));
+        assert_eq!(drbg.generate(&mut [0x00], &[]), Err(Error::ReseedRequired));
+
+        drbg.reseed(&[0x42; 16], &[]).unwrap();
+        assert_eq!(drbg.generate(&mut [0x00], &[]), Ok(()));
+    }
+
+    #[cfg(feature = "rand_core")]
+    #[test]
+    fn test_rand_core() {
+        use rand_core::{RngCore, SeedableRng};
+
+        let mut rng = CtrDrbg::<u32>::from_seed(bytes(0x00..0x20).try_into().unwrap());
+        let mut output = [0x00; 16];
+        rng.fill_bytes(&mut output);
+        assert_eq!(hex::encode(output), "7273e44c07613e742aaee24b4ef33782");
+        let mut output = [0x00; 3];
+        rng.fill_bytes(&mut output);
+        assert_eq!(hex::encode(output), "c7641e");
+        assert_eq!(
+            rng.next_u64(),
+            u64::from_le_bytes(hex::decode("2ae25cf46644ebd0").unwrap().try_into().unwrap())
+        );
+        // All three requests come from the same buffer
+        assert_eq!(rng.reseed_counter(), 2);
+
+        // Once the buffer is spent, long requests are generated directly
+        rng.fill_bytes(&mut [0x00; RNG_BUFFER_LEN - 27]);
+        assert_eq!(rng.reseed_counter(), 2);
+        let mut output = [0x00; 300];
+        rng.fill_bytes(&mut output);
+        assert_eq!(hex::encode(&output[..8]), "7882c3d952e2aefe");
+        assert_eq!(rng.reseed_counter(), 3);
+
+        let mut rng = CtrDrbg::<u64>::seed_from_u64(42).with_reseed_interval(0);
+        let mut output = [0x00; 4];
+        assert!(rng.try_fill_bytes(&mut output).is_err());
+    }
+}
@@ -0,0 +1,178 @@
+use std::marker::PhantomData;
+
+use crate::{
+    key::Key, mac::encode_bytes, schedule::KeySchedule, settings::Rc5Settings, word::Word,
+};
+
+mod davies_meyer;
+pub use davies_meyer::DaviesMeyer;
+
+mod matyas_meyer_oseas;
+pub use matyas_meyer_oseas::MatyasMeyerOseas;
+
+mod mdc2;
+pub use mdc2::Mdc2;
+
+/// Compression function of a hash built on RC5
+///
+/// Message blocks and chaining values are RC5 blocks of the chosen [`Word`],
+/// the cipher is re-keyed with a block of one of them on every call.
+/// [`Rc5Hasher`] does the buffering and the Merkle–Damgård strengthening
+pub trait Compression {
+    /// Count of blocks in the chaining value, which is also the digest
+    const STATE_BLOCKS: usize = 1;
+
+    /// Chaining value before the first block
+    fn initial_state<W: Word>() -> Vec<u8>;
+
+    /// Absorb one message `block` into the chaining value `state`
+    fn compress<W: Word>(state: &mut [u8], block: &[u8], rounds_count: u8);
+}
+
+/// Incremental Merkle–Damgård hash over a [`Compression`] function
+///
+/// The message is padded by `0x80`, zeros and its length in bits
+/// as a big-endian `u64`, so messages of different lengths don't collide trivially
+pub struct Rc5Hasher<W: Word, F: Compression> {
+    rounds_count: u8,
+    state: Vec<u8>,
+    pending: Vec<u8>,
+    /// Length of the whole message in bytes
+    len: u64,
+    _p: PhantomData<(W, F)>,
+}
+
+impl<W: Word, F: Compression> Rc5Hasher<W, F> {
+    /// Hasher with RC5 of `settings` as the block cipher, over an empty message
+    pub fn new(settings: Rc5Settings<W>) -> Self {
+        Self {
+            rounds_count: settings.rounds_count,
+            state: F::initial_state::<W>(),
+            pending: Vec::with_capacity(W::BLOCK_BYTES),
+            len: 0,
+            _p: PhantomData::default(),
+        }
+    }
+
+    /// Length of the digest in bytes
+    pub fn digest_len() -> usize {
+        F::STATE_BLOCKS * W::BLOCK_BYTES
+    }
+
+    /// Hash of the whole `input` at once
+    pub fn digest(settings: Rc5Settings<W>, input: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new(settings);
+        hasher.update(input);
+        hasher.finalize()
+    }
+
+    /// Add `input` to the hashed message
+    pub fn update(&mut self, mut input: &[u8]) {
+        self.len = self.len.wrapping_add(input.len() as u64);
+        while !input.is_empty() {
+            let take = (W::BLOCK_BYTES - self.pending.len()).min(input.len());
+            self.pending.extend_from_slice(&input[..take]);
+            input = &input[take..];
+
+            if self.pending.len() == W::BLOCK_BYTES {
+                F::compress::<W>(&mut self.state, &self.pending, self.rounds_count);
+                self.pending.clear();
+            }
+        }
+    }
+
+    /// The digest: the final chaining value, [`Compression::STATE_BLOCKS`] blocks long
+    pub fn finalize(mut self) -> Vec<u8> {
+        let mut padding = vec![0x80];
+        padding.resize(padding_len(W::BLOCK_BYTES, self.pending.len()), 0x00);
+        padding.extend((self.len.wrapping_mul(8)).to_be_bytes());
+
+        let len = self.len;
+        self.update(&padding);
+        self.len = len;
+        debug_assert!(self.pending.is_empty());
+        debug_assert_eq!(self.state.len(), Self::digest_len());
+        self.state
+    }
+}
+
+/// Bytes of `0x80` and zeros, so that with the 8-byte length
+/// the message ends at the block boundary
+fn padding_len(block_len: usize, pending: usize) -> usize {
+    1 + (block_len - (pending + 1 + 8) % block_len) % block_len
+}
+
+/// Block-sized key, to re-key RC5 with a block of the message or the chaining value
+struct BlockKey<'b, W: Word> {
+    bytes: &'b [u8],
+    _p: PhantomData<W>,
+}
+
+impl<W: Word> Key for BlockKey<'_, W> {
+    const SIZE_HINT: u8 = W::BLOCK_BYTES as u8;
+
+    fn secret(&self) -> &[u8] {
+        self.bytes
+    }
+}
+
+/// `E_key(input) ^ input`
+fn feed_forward<W: Word>(key: &[u8], input: &[u8], rounds_count: u8) -> Vec<u8> {
+    let key = BlockKey::<W> {
+        bytes: key,
+        _p: PhantomData::default(),
+    };
+    let schedule = KeySchedule::new(key, Rc5Settings::<W>::new(rounds_count));
+
+    let mut output = input.to_vec();
+    encode_bytes(&schedule, &mut output);
+    output
+        .iter_mut()
+        .zip(input)
+        .for_each(|(lhs, rhs)| *lhs ^= rhs);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_padding_len() {
+        for block_len in [2, 4, 8, 16, 32] {
+            for pending in 0..block_len {
+                let len = padding_len(block_len, pending);
+                assert!(len >= 1, "{block_len} {pending}");
+                assert_eq!((pending + len + 8) % block_len, 0, "{block_len} {pending}");
+                assert!(len <= block_len.max(8) + 1, "{block_len} {pending}");
+            }
+        }
+        assert_eq!(padding_len(8, 0), 8);
+        assert_eq!(padding_len(8, 7), 1);
+        assert_eq!(padding_len(16, 7), 1);
+        assert_eq!(padding_len(16, 8), 16);
+    }
+
+    #[test]
+    fn test_incremental() {
+        let input = (0..100).collect::<Vec<u8>>();
+        let digest = Rc5Hasher::<u16, Mdc2>::digest(Rc5Settings::new(12), &input);
+        for step in [1, 3, 4, 7, 64] {
+            let mut hasher = Rc5Hasher::<u16, Mdc2>::new(Rc5Settings::new(12));
+            input.chunks(step).for_each(|chunk| hasher.update(chunk));
+            assert_eq!(hasher.finalize(), digest, "step {step}");
+        }
+        assert_eq!(digest.len(), Rc5Hasher::<u16, Mdc2>::digest_len());
+        assert_eq!(Rc5Hasher::<u64, DaviesMeyer>::digest_len(), 16);
+    }
+
+    #[test]
+    fn test_strengthening() {
+        // Without the length these would collide
+        let digest =
+            |input: &[u8]| Rc5Hasher::<u32, DaviesMeyer>::digest(Rc5Settings::default(), input);
+        assert_ne!(digest(&[]), digest(&[0x80]));
+        assert_ne!(digest(&[0x01]), digest(&[0x01, 0x00]));
+        assert_ne!(digest(&[0x00; 8]), digest(&[0x00; 16]));
+    }
+}
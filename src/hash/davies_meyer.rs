@@ -0,0 +1,48 @@
+use crate::{
+    hash::{feed_forward, Compression},
+    word::Word,
+};
+
+/// Davies–Meyer: `H_i = E_{m_i}(H_{i-1}) ^ H_{i-1}`, with the zero initial value
+///
+/// Every message block is the key, so the key schedule is computed for every block
+pub struct DaviesMeyer;
+
+impl Compression for DaviesMeyer {
+    fn initial_state<W: Word>() -> Vec<u8> {
+        vec![0x00; W::BLOCK_BYTES]
+    }
+
+    fn compress<W: Word>(state: &mut [u8], block: &[u8], rounds_count: u8) {
+        let next = feed_forward::<W>(block, state, rounds_count);
+        state.copy_from_slice(&next);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hash::Rc5Hasher, settings::Rc5Settings};
+
+    fn digest<W: Word>(settings: Rc5Settings<W>, len: u8) -> String {
+        let input = (0..len).collect::<Vec<u8>>();
+        hex::encode(Rc5Hasher::<W, DaviesMeyer>::digest(settings, &input))
+    }
+
+    #[test]
+    fn test_vectors() {
+        assert_eq!(digest(Rc5Settings::default(), 0), "d8ee1873847613af");
+        assert_eq!(digest(Rc5Settings::default(), 3), "0542fcf01b948065");
+        assert_eq!(digest(Rc5Settings::default(), 20), "798cb89ea66ed4dc");
+        assert_eq!(digest(Rc5Settings::<u8>::new(12), 5), "1112");
+        assert_eq!(digest(Rc5Settings::<u16>::new(12), 9), "2ba2d20b");
+        assert_eq!(
+            digest(Rc5Settings::<u64>::new(16), 40),
+            "6006345fde2cf63209c8a7722a207519"
+        );
+        assert_eq!(
+            digest(Rc5Settings::<u128>::new(20), 70),
+            "3629c1035c7d209a7a3a5bec7d8cb24c065a59649303cc5ba4c36a67050f1533"
+        );
+    }
+}
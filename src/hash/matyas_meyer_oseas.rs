@@ -0,0 +1,48 @@
+use crate::{
+    hash::{feed_forward, Compression},
+    word::Word,
+};
+
+/// Matyas–Meyer–Oseas: `H_i = E_{H_{i-1}}(m_i) ^ m_i`, with the zero initial value
+///
+/// The chaining value is the key, so the key schedule is computed for every block
+pub struct MatyasMeyerOseas;
+
+impl Compression for MatyasMeyerOseas {
+    fn initial_state<W: Word>() -> Vec<u8> {
+        vec![0x00; W::BLOCK_BYTES]
+    }
+
+    fn compress<W: Word>(state: &mut [u8], block: &[u8], rounds_count: u8) {
+        let next = feed_forward::<W>(state, block, rounds_count);
+        state.copy_from_slice(&next);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hash::Rc5Hasher, settings::Rc5Settings};
+
+    fn digest<W: Word>(settings: Rc5Settings<W>, len: u8) -> String {
+        let input = (0..len).collect::<Vec<u8>>();
+        hex::encode(Rc5Hasher::<W, MatyasMeyerOseas>::digest(settings, &input))
+    }
+
+    #[test]
+    fn test_vectors() {
+        assert_eq!(digest(Rc5Settings::default(), 0), "11cb4db45619cf62");
+        assert_eq!(digest(Rc5Settings::default(), 3), "59bc47ac064d4663");
+        assert_eq!(digest(Rc5Settings::default(), 20), "70bf0024b09672c9");
+        assert_eq!(digest(Rc5Settings::<u8>::new(12), 5), "2aaa");
+        assert_eq!(digest(Rc5Settings::<u16>::new(12), 9), "10fe06da");
+        assert_eq!(
+            digest(Rc5Settings::<u64>::new(16), 40),
+            "47be10f160c952529565cceabb90659d"
+        );
+        assert_eq!(
+            digest(Rc5Settings::<u128>::new(20), 70),
+            "eb4999e8e5605cc3e0cc9812609b06892b5c2999e21da988e4b81532d6a63721"
+        );
+    }
+}
@@ -13,11 +13,11 @@ pub trait Key {
 /// Converting the Secret Key from Bytes to Words
 ///
 /// Copy the Secret key `K[0..b-1]` into an array `L[0..c-1]`
-/// of `c = [b/u]` words, where `u = w/8` is the number of bytes\words.
+/// of `c = ⌈b/u⌉` words, where `u = w/8` is the number of bytes\words.
 /// Any unfilled byte positions of `L` are zeroes. In the case that
 /// `b = c = 0` we reset `c` to `1` and set `L[0]` to zero.
 fn expand_key_to_words<W: Word, K: Key>(key: &K) -> Vec<W> {
-    let len = K::SIZE_HINT.max(1) as usize / W::BYTES;
+    let len = (K::SIZE_HINT.max(1) as usize + W::BYTES - 1) / W::BYTES;
     let mut words = vec![W::zero(); len];
 
     for index_secret in (0..K::SIZE_HINT).rev() {
@@ -174,6 +174,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_expand_short_key() {
+        assert_eq!(expand_key_to_words::<u32, [u8; 1]>(&[0x11]), [0x11]);
+        assert_eq!(
+            expand_key_to_words::<u32, [u8; 5]>(&[0x01, 0x02, 0x03, 0x04, 0x05]),
+            [0x04030201, 0x05]
+        );
+        assert_eq!(
+            expand_key_to_words::<u128, [u8; 3]>(&[0x01, 0x02, 0x03]),
+            [0x030201]
+        );
+    }
+
     #[test]
     fn test_mixin() {
         let key: [u8; 128] = (0..128).collect::<Vec<_>>().try_into().unwrap();
@@ -9,20 +9,86 @@ pub use key::Key;
 mod word;
 pub use word::Word;
 
+mod aead;
+pub use aead::{Ccm, Eax, Gcm, Gmac, Ocb, Siv};
+
 mod block;
 use block::{DecodeAsBlocks, EncodeAsBlocks};
 
+mod cipher;
+pub use cipher::{DecodeBlock, EncodeBlock, Rc5Decryptor, Rc5Encryptor};
+
+mod drbg;
+pub use drbg::CtrDrbg;
+
+mod hash;
+pub use hash::{Compression, DaviesMeyer, MatyasMeyerOseas, Mdc2, Rc5Hasher};
+
+mod mac;
+pub use mac::{CbcMac, Cmac, Iso9797Padding, Pmac, Poly1305, RetailMac};
+
+mod mode;
+#[cfg(any(test, feature = "test-util"))]
+pub use mode::conformance;
+pub use mode::{
+    BlockMode, CbcDecoder, CbcEncoder, CbcPadDecoder, CbcPadEncoder, CfbDecoder, CfbEncoder, Ctr,
+    CtsDecoder, CtsEncoder, CtsVariant, Direction, EcbDecoder, EcbEncoder, IgeDecoder, IgeEncoder,
+    IvGuard, ModeIv, ModeStream, Ofb, PcbcDecoder, PcbcEncoder, StreamMode,
+};
+
+mod padding;
+pub use padding::{AnsiX923, Iso10126, Iso7816, Padding, Pkcs7, ZeroPadding};
+
+mod schedule;
+pub use schedule::KeySchedule;
+
 mod settings;
 pub use settings::{DefaultWord, Rc5Settings};
 
+mod trace;
+pub use trace::{NoTrace, Observer, Register, TraceEvent, TraceRecord, TraceRecorder};
+
 #[derive(Debug, PartialEq, Eq)]
-/// Unfortunately, constant calculations in Rust
-/// are not yet stable enough to accept only arrays
-/// of the required length as input, so the trait-method
-/// have to return an error
+/// Slices can't be checked at compile time,
+/// so the slice-based trait-methods have to return an error.
+/// If the input size is known in advance, use [`EncodeRc5Array`]
+/// & [`DecodeRc5Array`], which check it at compile time instead
 pub enum Error {
     /// The input data must be a multiple of the word bytes len
     WrongInputSize,
+    /// The initialization vector must be exactly one block (pair of words) long
+    WrongIvSize,
+    /// The padding of the decoded data is malformed
+    WrongPadding,
+    /// The serialized key schedule has wrong magic bytes or length
+    MalformedSchedule,
+    /// The serialized key schedule has a format version unknown to this crate
+    UnsupportedScheduleVersion(u8),
+    /// The serialized key schedule was made for another word size
+    ScheduleWordSizeMismatch { expected: u8, actual: u8 },
+    /// The nonce must leave at least one byte of the block for the counter
+    WrongNonceSize,
+    /// The counter of a stream mode can't address the requested position
+    CounterOverflow,
+    /// The feedback segment must be a whole count of bytes, from one byte to the block size
+    WrongSegmentSize,
+    /// The initialization vector was already used, reported by [`IvGuard`]
+    IvReused,
+    /// The tag length is not supported by the authenticated mode
+    WrongTagSize,
+    /// The tag doesn't match: the ciphertext, the nonce or the associated data
+    /// are corrupted or forged, so nothing is decrypted
+    AuthenticationFailed,
+    /// The mode isn't defined for the block size of this word
+    WrongBlockSize,
+    /// The length field of CCM must be from 2 to 8 bytes and leave room for the nonce
+    WrongLengthSize,
+    /// SIV takes one less component than the block has bits, the plaintext included
+    TooManyComponents,
+    /// The entropy input is shorter than the security strength of the generator
+    WrongSeedSize,
+    /// The reseed interval of the generator has passed
+    ReseedRequired,
 }
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -61,6 +127,34 @@ pub trait EncodeRc5 {
     fn encode_rc5(&self, key: impl Key) -> Result<Vec<u8>, Error> {
         self.encode_rc5_with_settings(key, Rc5Settings::default())
     }
+
+    /// Encode by RC5 with custom settings and report
+    /// every step of every block to `observer`
+    ///
+    /// Pass a [`TraceRecorder`] to get the whole trace as CSV or JSON
+    /// `Error` - if `&self` cannot be divided into blocks!
+    fn encode_rc5_traced<W: Word>(
+        &self,
+        key: impl Key,
+        settings: Rc5Settings<W>,
+        observer: &mut impl Observer<W>,
+    ) -> Result<Vec<u8>, Error>;
+
+    /// Pad `Self` with `padding` and encode by RC5 with custom settings
+    ///
+    /// Unlike [`EncodeRc5::encode_rc5_with_settings`]
+    /// the input can be of any length
+    fn encode_rc5_padded_with_settings<W: Word>(
+        &self,
+        key: impl Key,
+        settings: Rc5Settings<W>,
+        padding: impl Padding,
+    ) -> Vec<u8>;
+
+    /// Pad `Self` with `padding` and encode by RC5 with default settings (32/12/b)
+    fn encode_rc5_padded(&self, key: impl Key, padding: impl Padding) -> Vec<u8> {
+        self.encode_rc5_padded_with_settings(key, Rc5Settings::default(), padding)
+    }
 }
 
 pub trait DecodeRc5 {
@@ -85,9 +179,117 @@ pub trait DecodeRc5 {
     fn decode_rc5(&self, key: impl Key) -> Result<Vec<u8>, Error> {
         self.decode_rc5_with_settings(key, Rc5Settings::default())
     }
+
+    /// Decode by RC5 with custom settings and report
+    /// every step of every block to `observer`
+    ///
+    /// Pass a [`TraceRecorder`] to get the whole trace as CSV or JSON
+    /// `Error` - if `&self` cannot be divided into blocks!
+    fn decode_rc5_traced<W: Word>(
+        &self,
+        key: impl Key,
+        settings: Rc5Settings<W>,
+        observer: &mut impl Observer<W>,
+    ) -> Result<Vec<u8>, Error>;
+
+    /// Decode by RC5 with custom settings and remove `padding`
+    ///
+    /// `Error::WrongInputSize` - if `&self` cannot be divided into blocks!
+    /// `Error::WrongPadding` - if the decoded padding is malformed
+    fn decode_rc5_padded_with_settings<W: Word>(
+        &self,
+        key: impl Key,
+        settings: Rc5Settings<W>,
+        padding: impl Padding,
+    ) -> Result<Vec<u8>, Error>;
+
+    /// Decode by RC5 with default settings (32/12/b) and remove `padding`
+    ///
+    /// Check [`DecodeRc5::decode_rc5_padded_with_settings`] for errors
+    fn decode_rc5_padded(&self, key: impl Key, padding: impl Padding) -> Result<Vec<u8>, Error> {
+        self.decode_rc5_padded_with_settings(key, Rc5Settings::default(), padding)
+    }
+}
+
+/// Encode arrays by RC5
+///
+/// Unlike [`EncodeRc5`] the size of the input is checked at compile time:
+/// `N` must be a multiple of the block size (pair of words),
+/// so there is no error to return.
+/// ```compile_fail
+/// use rc5_cypher::*;
+///
+/// // 6 bytes is not a multiple of 8 bytes RC5-32 block
+/// [0x00; 6].encode_rc5_array([0x00; 16]);
+/// ```
+pub trait EncodeRc5Array<const N: usize> {
+    /// Encode by RC5 with custom settings
+    ///
+    /// Check 4.1 in [the specification](https://www.grc.com/r&d/rc5.pdf).
+    fn encode_rc5_array_with_settings<W: Word>(
+        &self,
+        key: impl Key,
+        settings: Rc5Settings<W>,
+    ) -> [u8; N];
+
+    /// Encode by RC5 with default settings (32/12/b)
+    ///
+    /// Check 4.1 in [the specification](https://www.grc.com/r&d/rc5.pdf).
+    fn encode_rc5_array(&self, key: impl Key) -> [u8; N] {
+        self.encode_rc5_array_with_settings(key, Rc5Settings::default())
+    }
 }
 
-impl<T: EncodeAsBlocks> EncodeRc5 for T {
+/// Decode arrays by RC5
+///
+/// Unlike [`DecodeRc5`] the size of the input is checked at compile time:
+/// `N` must be a multiple of the block size (pair of words),
+/// so there is no error to return.
+/// ```compile_fail
+/// use rc5_cypher::*;
+///
+/// // 6 bytes is not a multiple of 8 bytes RC5-32 block
+/// [0x00; 6].decode_rc5_array([0x00; 16]);
+/// ```
+pub trait DecodeRc5Array<const N: usize> {
+    /// Decode by RC5 with custom settings
+    ///
+    /// Check 4.2 in [the specification](https://www.grc.com/r&d/rc5.pdf).
+    fn decode_rc5_array_with_settings<W: Word>(
+        &self,
+        key: impl Key,
+        settings: Rc5Settings<W>,
+    ) -> [u8; N];
+
+    /// Decode by RC5 with default settings (32/12/b)
+    ///
+    /// Check 4.2 in [the specification](https://www.grc.com/r&d/rc5.pdf).
+    fn decode_rc5_array(&self, key: impl Key) -> [u8; N] {
+        self.decode_rc5_array_with_settings(key, Rc5Settings::default())
+    }
+}
+
+impl<const N: usize> EncodeRc5Array<N> for [u8; N] {
+    fn encode_rc5_array_with_settings<W: Word>(
+        &self,
+        key: impl Key,
+        settings: Rc5Settings<W>,
+    ) -> [u8; N] {
+        KeySchedule::new(key, settings).encode_array(*self)
+    }
+}
+
+impl<const N: usize> DecodeRc5Array<N> for [u8; N] {
+    fn decode_rc5_array_with_settings<W: Word>(
+        &self,
+        key: impl Key,
+        settings: Rc5Settings<W>,
+    ) -> [u8; N] {
+        KeySchedule::new(key, settings).decode_array(*self)
+    }
+}
+
+impl<T: AsRef<[u8]>> EncodeRc5 for T {
     fn encode_rc5_with_settings<W: Word>(
         &self,
         key: impl Key,
@@ -95,9 +297,30 @@ impl<T: EncodeAsBlocks> EncodeRc5 for T {
     ) -> Result<Vec<u8>, Error> {
         Ok(self.encode_as_blocks::<W>(key, settings.rounds_count)?)
     }
+
+    fn encode_rc5_traced<W: Word>(
+        &self,
+        key: impl Key,
+        settings: Rc5Settings<W>,
+        observer: &mut impl Observer<W>,
+    ) -> Result<Vec<u8>, Error> {
+        Ok(self.encode_as_blocks_observed::<W>(key, settings.rounds_count, observer)?)
+    }
+
+    fn encode_rc5_padded_with_settings<W: Word>(
+        &self,
+        key: impl Key,
+        settings: Rc5Settings<W>,
+        mut padding: impl Padding,
+    ) -> Vec<u8> {
+        padding
+            .pad(self.as_ref(), W::BLOCK_BYTES)
+            .encode_as_blocks::<W>(key, settings.rounds_count)
+            .expect("Padded to the block size")
+    }
 }
 
-impl<T: DecodeAsBlocks> DecodeRc5 for T {
+impl<T: AsRef<[u8]>> DecodeRc5 for T {
     fn decode_rc5_with_settings<W: Word>(
         &self,
         key: impl Key,
@@ -105,6 +328,25 @@ impl<T: DecodeAsBlocks> DecodeRc5 for T {
     ) -> Result<Vec<u8>, Error> {
         Ok(self.decode_as_blocks::<W>(key, settings.rounds_count)?)
     }
+
+    fn decode_rc5_traced<W: Word>(
+        &self,
+        key: impl Key,
+        settings: Rc5Settings<W>,
+        observer: &mut impl Observer<W>,
+    ) -> Result<Vec<u8>, Error> {
+        Ok(self.decode_as_blocks_observed::<W>(key, settings.rounds_count, observer)?)
+    }
+
+    fn decode_rc5_padded_with_settings<W: Word>(
+        &self,
+        key: impl Key,
+        settings: Rc5Settings<W>,
+        padding: impl Padding,
+    ) -> Result<Vec<u8>, Error> {
+        let decoded = self.decode_as_blocks::<W>(key, settings.rounds_count)?;
+        Ok(padding.unpad(&decoded, W::BLOCK_BYTES)?.to_vec())
+    }
 }
 
 #[cfg(test)]
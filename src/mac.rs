@@ -0,0 +1,146 @@
+use crate::{
+    block::{read_block, write_block},
+    cipher::{DecodeBlock, EncodeBlock},
+    word::Word,
+};
+
+mod cbc_mac;
+pub use cbc_mac::{CbcMac, Iso9797Padding, RetailMac};
+
+mod cmac;
+pub(crate) use cmac::cmac_subkeys;
+pub use cmac::Cmac;
+
+mod pmac;
+pub use pmac::Pmac;
+
+mod poly1305;
+pub use poly1305::Poly1305;
+
+/// Encode a block given as bytes
+pub(crate) fn encode_bytes<W: Word>(cipher: &impl EncodeBlock<W>, block: &mut [u8]) {
+    let encoded = cipher.encode_block(read_block(block));
+    write_block(encoded, block);
+}
+
+/// Decode a block given as bytes
+pub(crate) fn decode_bytes<W: Word>(cipher: &impl DecodeBlock<W>, block: &mut [u8]) {
+    let decoded = cipher.decode_block(read_block(block));
+    write_block(decoded, block);
+}
+
+/// Multiply the block by `x` in `GF(2^n)`, where `n` is the block size in bits
+///
+/// The block is a big-endian polynomial, reduced by the low-weight irreducible
+/// polynomial of its size: `x^16 + x^5 + x^3 + x + 1`, `x^32 + x^7 + x^3 + x^2 + 1`,
+/// `x^64 + x^4 + x^3 + x + 1`, `x^128 + x^7 + x^2 + x + 1` or `x^256 + x^10 + x^5 + x^2 + 1`
+pub(crate) fn double(block: &mut [u8]) {
+    let reduction = reduction(block.len());
+    let carry = block[0] >> 7;
+    for index in 0..block.len() - 1 {
+        block[index] = (block[index] << 1) | (block[index + 1] >> 7);
+    }
+    let last = block.len() - 1;
+    block[last] <<= 1;
+
+    // No branch on the secret carry
+    let mask = 0u16.wrapping_sub(carry as u16);
+    block[last] ^= (reduction & mask) as u8;
+    block[last - 1] ^= ((reduction & mask) >> 8) as u8;
+}
+
+/// Divide the block by `x` in `GF(2^n)`, the inverse of [`double`]
+pub(crate) fn halve(block: &mut [u8]) {
+    let reduction = reduction(block.len());
+    let last = block.len() - 1;
+    let carry = block[last] & 0x01;
+
+    // No branch on the secret carry
+    let mask = 0u16.wrapping_sub(carry as u16);
+    block[last] ^= (reduction & mask) as u8;
+    block[last - 1] ^= ((reduction & mask) >> 8) as u8;
+
+    for index in (1..block.len()).rev() {
+        block[index] = (block[index] >> 1) | (block[index - 1] << 7);
+    }
+    block[0] = (block[0] >> 1) | (carry << 7);
+}
+
+/// Low bits of the irreducible polynomial for a block of `len` bytes
+fn reduction(len: usize) -> u16 {
+    match len {
+        2 => 0x002b,
+        4 => 0x008d,
+        8 => 0x001b,
+        16 => 0x0087,
+        32 => 0x0425,
+        len => unreachable!("No block of {len} bytes"),
+    }
+}
+
+/// Compare tags, the time doesn't depend on where they differ
+pub(crate) fn constant_time_eq(lhs: &[u8], rhs: &[u8]) -> bool {
+    lhs.len() == rhs.len()
+        && lhs
+            .iter()
+            .zip(rhs)
+            .fold(0x00, |diff, (lhs, rhs)| diff | (lhs ^ rhs))
+            == 0x00
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_double() {
+        let mut block = [0x40, 0x01];
+        double(&mut block);
+        assert_eq!(block, [0x80, 0x02]);
+        double(&mut block);
+        assert_eq!(block, [0x00, 0x04 ^ 0x2b]);
+
+        let mut block = [0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01];
+        double(&mut block);
+        assert_eq!(
+            block,
+            [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1b ^ 0x02]
+        );
+
+        let mut block = [0x00; 32];
+        block[0] = 0x80;
+        double(&mut block);
+        assert_eq!(block[30..], [0x04, 0x25]);
+        assert_eq!(block[..30], [0x00; 30]);
+    }
+
+    #[test]
+    fn test_halve() {
+        let mut block = [0x00, 0x04 ^ 0x2b];
+        halve(&mut block);
+        assert_eq!(block, [0x80, 0x02]);
+        halve(&mut block);
+        assert_eq!(block, [0x40, 0x01]);
+
+        for len in [2, 4, 8, 16, 32] {
+            let original = (0..len as u8)
+                .map(|byte| byte.wrapping_mul(37).wrapping_add(1))
+                .collect::<Vec<_>>();
+            let mut block = original.clone();
+            halve(&mut block);
+            double(&mut block);
+            assert_eq!(block, original);
+            double(&mut block);
+            halve(&mut block);
+            assert_eq!(block, original);
+        }
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(&[0x01, 0x02], &[0x01, 0x02]));
+        assert!(!constant_time_eq(&[0x01, 0x02], &[0x01, 0x03]));
+        assert!(!constant_time_eq(&[0x01, 0x02], &[0x01]));
+        assert!(constant_time_eq(&[], &[]));
+    }
+}
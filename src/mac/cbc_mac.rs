@@ -0,0 +1,358 @@
+use std::marker::PhantomData;
+
+use crate::{
+    block::xor_bytes,
+    cipher::{DecodeBlock, EncodeBlock},
+    mac::{constant_time_eq, decode_bytes, encode_bytes},
+    padding::{Iso7816, Padding, ZeroPadding},
+    word::Word,
+    Error,
+};
+
+/// Padding methods of [ISO/IEC 9797-1](https://www.iso.org/standard/50375.html)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Iso9797Padding {
+    /// Zeros up to the block boundary, an empty message becomes one zero block
+    Method1,
+    /// `0x80` and zeros up to the block boundary, always added
+    Method2,
+    /// A block with the message length in bits, then the message padded by zeros
+    Method3,
+}
+
+impl Iso9797Padding {
+    /// `Error::WrongInputSize` - if the length in bits doesn't fit a block ([`Self::Method3`])
+    fn pad(self, message: &[u8], block_len: usize) -> Result<Vec<u8>, Error> {
+        match self {
+            Self::Method1 if message.is_empty() => Ok(vec![0x00; block_len]),
+            Self::Method1 => Ok(ZeroPadding.pad(message, block_len)),
+            Self::Method2 => Ok(Iso7816.pad(message, block_len)),
+            Self::Method3 => {
+                let bits = message.len() as u128 * 8;
+                if block_len < 16 && bits >> (8 * block_len) != 0 {
+                    return Err(Error::WrongInputSize);
+                }
+                let mut padded = vec![0x00; block_len.saturating_sub(16)];
+                padded.extend_from_slice(&bits.to_be_bytes()[16 - block_len.min(16)..]);
+                padded.extend(ZeroPadding.pad(message, block_len));
+                Ok(padded)
+            }
+        }
+    }
+}
+
+/// CBC-MAC with the message length prepended (ISO/IEC 9797-1 MAC algorithm 1
+/// with padding method 3)
+///
+/// Plain CBC-MAC is secure only for messages of one fixed length: a tag of one message
+/// lets one forge a tag of its extension. The length block at the start makes
+/// messages of different lengths unrelated
+pub struct CbcMac<W: Word, C: EncodeBlock<W>> {
+    cipher: C,
+    tag_len: usize,
+    _p: PhantomData<W>,
+}
+
+impl<W: Word, C: EncodeBlock<W>> CbcMac<W, C> {
+    /// CBC-MAC with the full-block tag
+    pub fn new(cipher: C) -> Self {
+        Self {
+            cipher,
+            tag_len: W::BLOCK_BYTES,
+            _p: PhantomData::default(),
+        }
+    }
+
+    /// CBC-MAC with the tag truncated to `tag_len` bytes
+    ///
+    /// `Error` - if `tag_len` is zero or more than the block size
+    pub fn with_tag_len(cipher: C, tag_len: usize) -> Result<Self, Error> {
+        check_tag_len::<W>(tag_len)?;
+        Ok(Self {
+            tag_len,
+            ..Self::new(cipher)
+        })
+    }
+
+    /// Tag of the `message`
+    ///
+    /// `Error::WrongInputSize` - if the length of `message` in bits doesn't fit a block
+    pub fn compute(&self, message: &[u8]) -> Result<Vec<u8>, Error> {
+        let padded = Iso9797Padding::Method3.pad(message, W::BLOCK_BYTES)?;
+        let mut tag = chain(&self.cipher, &padded);
+        tag.truncate(self.tag_len);
+        Ok(tag)
+    }
+
+    /// Check the tag of the `message` in constant time
+    ///
+    /// `Error::AuthenticationFailed` - if the tag doesn't match
+    pub fn verify(&self, message: &[u8], tag: &[u8]) -> Result<(), Error> {
+        check_tag(&self.compute(message)?, tag)
+    }
+}
+
+/// Retail MAC: ISO/IEC 9797-1 MAC algorithm 3 (ANSI X9.19) over RC5
+///
+/// CBC-MAC under the first key, then the last block is decrypted with the second key
+/// and encrypted with the first one again
+pub struct RetailMac<W: Word, C: EncodeBlock<W>, D: DecodeBlock<W>> {
+    cipher: C,
+    second_cipher: D,
+    padding: Iso9797Padding,
+    tag_len: usize,
+    _p: PhantomData<W>,
+}
+
+impl<W: Word, C: EncodeBlock<W>, D: DecodeBlock<W>> RetailMac<W, C, D> {
+    /// Retail MAC with the full-block tag
+    pub fn new(cipher: C, second_cipher: D, padding: Iso9797Padding) -> Self {
+        Self {
+            cipher,
+            second_cipher,
+            padding,
+            tag_len: W::BLOCK_BYTES,
+            _p: PhantomData::default(),
+        }
+    }
+
+    /// Retail MAC with the tag truncated to `tag_len` bytes
+    ///
+    /// `Error` - if `tag_len` is zero or more than the block size
+    pub fn with_tag_len(
+        cipher: C,
+        second_cipher: D,
+        padding: Iso9797Padding,
+        tag_len: usize,
+    ) -> Result<Self, Error> {
+        check_tag_len::<W>(tag_len)?;
+        Ok(Self {
+            tag_len,
+            ..Self::new(cipher, second_cipher, padding)
+        })
+    }
+
+    /// Tag of the `message`
+    ///
+    /// `Error::WrongInputSize` - if the length of `message` in bits doesn't fit a block
+    /// ([`Iso9797Padding::Method3`] only)
+    pub fn compute(&self, message: &[u8]) -> Result<Vec<u8>, Error> {
+        let padded = self.padding.pad(message, W::BLOCK_BYTES)?;
+        let mut tag = chain(&self.cipher, &padded);
+        decode_bytes(&self.second_cipher, &mut tag);
+        encode_bytes(&self.cipher, &mut tag);
+        tag.truncate(self.tag_len);
+        Ok(tag)
+    }
+
+    /// Check the tag of the `message` in constant time
+    ///
+    /// `Error::AuthenticationFailed` - if the tag doesn't match
+    pub fn verify(&self, message: &[u8], tag: &[u8]) -> Result<(), Error> {
+        check_tag(&self.compute(message)?, tag)
+    }
+}
+
+fn check_tag_len<W: Word>(tag_len: usize) -> Result<(), Error> {
+    match tag_len == 0 || tag_len > W::BLOCK_BYTES {
+        true => Err(Error::WrongTagSize),
+        false => Ok(()),
+    }
+}
+
+fn check_tag(expected: &[u8], tag: &[u8]) -> Result<(), Error> {
+    match constant_time_eq(expected, tag) {
+        true => Ok(()),
+        false => Err(Error::AuthenticationFailed),
+    }
+}
+
+/// CBC chain with the zero IV over already padded blocks, returns the last one
+fn chain<W: Word>(cipher: &impl EncodeBlock<W>, padded: &[u8]) -> Vec<u8> {
+    let mut state = vec![0x00; W::BLOCK_BYTES];
+    for block in padded.chunks(W::BLOCK_BYTES) {
+        xor_bytes(&mut state, block);
+        encode_bytes(cipher, &mut state);
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schedule::KeySchedule, settings::Rc5Settings};
+
+    fn keys() -> ([u8; 16], [u8; 16]) {
+        (
+            (0x00..0x10).collect::<Vec<_>>().try_into().unwrap(),
+            (0x20..0x30).collect::<Vec<_>>().try_into().unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_padding() {
+        use Iso9797Padding::*;
+
+        assert_eq!(Method1.pad(&[], 4), Ok(vec![0x00; 4]));
+        assert_eq!(Method1.pad(&[0xaa; 4], 4), Ok(vec![0xaa; 4]));
+        assert_eq!(
+            Method1.pad(&[0xaa; 5], 4),
+            Ok(hex::decode("aaaaaaaaaa000000").unwrap())
+        );
+        assert_eq!(Method2.pad(&[], 4), Ok(hex::decode("80000000").unwrap()));
+        assert_eq!(
+            Method2.pad(&[0xaa; 4], 4),
+            Ok(hex::decode("aaaaaaaa80000000").unwrap())
+        );
+        assert_eq!(Method3.pad(&[], 4), Ok(vec![0x00; 4]));
+        assert_eq!(
+            Method3.pad(&[0xaa; 5], 4),
+            Ok(hex::decode("00000028aaaaaaaaaa000000").unwrap())
+        );
+
+        let mut padded = vec![0x00; 31];
+        padded.push(0x08);
+        padded.extend(hex::decode("aa").unwrap());
+        padded.extend([0x00; 31]);
+        assert_eq!(Method3.pad(&[0xaa], 32), Ok(padded));
+
+        // 2^13 bytes is 2^16 bits
+        assert!(Method3.pad(&[0x00; 0x1fff], 2).is_ok());
+        assert_eq!(Method3.pad(&[0x00; 0x2000], 2), Err(Error::WrongInputSize));
+    }
+
+    #[test]
+    fn test_cbc_mac() {
+        let (key, _) = keys();
+        let input = (0..40).collect::<Vec<u8>>();
+
+        let schedule = KeySchedule::new(key, Rc5Settings::default());
+        let cbc_mac = CbcMac::new(&schedule);
+        assert_eq!(
+            hex::encode(cbc_mac.compute(&input[..20]).unwrap()),
+            "67915a25941da935"
+        );
+        assert_eq!(
+            hex::encode(cbc_mac.compute(&[]).unwrap()),
+            "b05f67ed0913b5a2"
+        );
+        assert_eq!(
+            hex::encode(cbc_mac.compute(&input[..16]).unwrap()),
+            "94eaa0beb570474a"
+        );
+
+        let schedule = KeySchedule::new(key, Rc5Settings::<u64>::new(16));
+        assert_eq!(
+            hex::encode(CbcMac::new(&schedule).compute(&input).unwrap()),
+            "056dd80fb9a5b6b1eb599e108631fca1"
+        );
+
+        let schedule = KeySchedule::new([0x00, 0x01, 0x02, 0x03], Rc5Settings::<u8>::new(12));
+        let cbc_mac = CbcMac::new(&schedule);
+        assert_eq!(hex::encode(cbc_mac.compute(&input[..5]).unwrap()), "4366");
+        assert_eq!(cbc_mac.compute(&[0x00; 0x2000]), Err(Error::WrongInputSize));
+    }
+
+    #[test]
+    fn test_length_extension() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::default());
+        let cbc_mac = CbcMac::new(&schedule);
+
+        // Without the length block the tag of `m || (m' ^ tag)` is predictable
+        let message = [0x01; 8];
+        let tag = cbc_mac.compute(&message).unwrap();
+        let mut forged = message.to_vec();
+        forged.extend(message.iter().zip(&tag).map(|(lhs, rhs)| lhs ^ rhs));
+        assert_ne!(cbc_mac.compute(&forged), Ok(tag.clone()));
+
+        // Zero bytes at the end change the length
+        assert_ne!(
+            cbc_mac.compute(&[0x01; 5]),
+            cbc_mac.compute(&[0x01, 0x01, 0x01, 0x01, 0x01, 0x00])
+        );
+        assert_eq!(cbc_mac.verify(&message, &tag), Ok(()));
+        assert_eq!(
+            cbc_mac.verify(&message[1..], &tag),
+            Err(Error::AuthenticationFailed)
+        );
+    }
+
+    #[test]
+    fn test_retail_mac() {
+        let (key, second_key) = keys();
+        let schedule = KeySchedule::new(key, Rc5Settings::default());
+        let second_schedule = KeySchedule::new(second_key, Rc5Settings::default());
+        let input = (0..40).collect::<Vec<u8>>();
+
+        for (padding, tags) in [
+            (
+                Iso9797Padding::Method1,
+                ["77cff97e68517ccf", "5c46d320da2a73df", "2df6e6d17a7fdfca"],
+            ),
+            (
+                Iso9797Padding::Method2,
+                ["e7c56b2eeefb2350", "90a58465d06e8da7", "0b4fea7d8eebe99c"],
+            ),
+            (
+                Iso9797Padding::Method3,
+                ["550c0aaaea8d9352", "5c46d320da2a73df", "fc0f366b34c3abb3"],
+            ),
+        ] {
+            let retail_mac = RetailMac::new(&schedule, &second_schedule, padding);
+            for (message, tag) in [&input[..20], &[], &input[..16]].into_iter().zip(tags) {
+                assert_eq!(
+                    hex::encode(retail_mac.compute(message).unwrap()),
+                    tag,
+                    "{padding:?}"
+                );
+            }
+        }
+
+        let schedule = KeySchedule::new(key, Rc5Settings::<u64>::new(16));
+        let second_schedule = KeySchedule::new(second_key, Rc5Settings::<u64>::new(16));
+        let retail_mac = RetailMac::new(&schedule, &second_schedule, Iso9797Padding::Method2);
+        assert_eq!(
+            hex::encode(retail_mac.compute(&input).unwrap()),
+            "f8dcec2f53df963283547912a62606e1"
+        );
+    }
+
+    #[test]
+    fn test_retail_mac_single_key() {
+        // With the same key the output transformation cancels out
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::default());
+        let retail_mac = RetailMac::new(&schedule, &schedule, Iso9797Padding::Method3);
+        let cbc_mac = CbcMac::new(&schedule);
+        assert_eq!(
+            retail_mac.compute(b"payment message"),
+            cbc_mac.compute(b"payment message")
+        );
+    }
+
+    #[test]
+    fn test_retail_mac_tag_len() {
+        let (key, second_key) = keys();
+        let schedule = KeySchedule::new(key, Rc5Settings::default());
+        let second_schedule = KeySchedule::new(second_key, Rc5Settings::default());
+        let retail_mac =
+            RetailMac::with_tag_len(&schedule, &second_schedule, Iso9797Padding::Method1, 4)
+                .unwrap();
+
+        let input = (0..20).collect::<Vec<u8>>();
+        let tag = retail_mac.compute(&input).unwrap();
+        assert_eq!(hex::encode(&tag), "77cff97e");
+        assert_eq!(retail_mac.verify(&input, &tag), Ok(()));
+        assert_eq!(
+            retail_mac.verify(&input, &hex::decode("77cff97e68517ccf").unwrap()),
+            Err(Error::AuthenticationFailed)
+        );
+        assert!(matches!(
+            RetailMac::with_tag_len(&schedule, &second_schedule, Iso9797Padding::Method1, 9),
+            Err(Error::WrongTagSize)
+        ));
+        assert!(matches!(
+            CbcMac::with_tag_len(&schedule, 0),
+            Err(Error::WrongTagSize)
+        ));
+    }
+}
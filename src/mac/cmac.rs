@@ -0,0 +1,206 @@
+use std::marker::PhantomData;
+
+use crate::{
+    block::xor_bytes,
+    cipher::EncodeBlock,
+    mac::{constant_time_eq, double, encode_bytes},
+    word::Word,
+    Error,
+};
+
+/// CMAC (OMAC1) from [NIST SP 800-38B](https://doi.org/10.6028/NIST.SP.800-38B)
+/// over a block of any size
+///
+/// The subkeys are derived with the polynomial of the block size,
+/// so the tags of 64-bit and 128-bit blocks match the usual CMAC
+pub struct Cmac<W: Word, C: EncodeBlock<W>> {
+    cipher: C,
+    tag_len: usize,
+    /// `K1` for the complete last block, `K2` for the padded one
+    subkeys: (Vec<u8>, Vec<u8>),
+    state: Vec<u8>,
+    /// The last block is kept until finalization, because it's XORed with a subkey
+    pending: Vec<u8>,
+    _p: PhantomData<W>,
+}
+
+impl<W: Word, C: EncodeBlock<W>> Cmac<W, C> {
+    /// CMAC with the full-block tag
+    pub fn new(cipher: C) -> Self {
+        let subkeys = cmac_subkeys(&cipher);
+        Self::with_subkeys(cipher, subkeys)
+    }
+
+    /// CMAC with the full-block tag and the `subkeys` already derived from `cipher`
+    pub(crate) fn with_subkeys(cipher: C, subkeys: (Vec<u8>, Vec<u8>)) -> Self {
+        Self {
+            cipher,
+            tag_len: W::BLOCK_BYTES,
+            subkeys,
+            state: vec![0x00; W::BLOCK_BYTES],
+            pending: Vec::with_capacity(W::BLOCK_BYTES),
+            _p: PhantomData::default(),
+        }
+    }
+
+    /// CMAC with the tag truncated to `tag_len` bytes
+    ///
+    /// `Error` - if `tag_len` is zero or more than the block size
+    pub fn with_tag_len(cipher: C, tag_len: usize) -> Result<Self, Error> {
+        if tag_len == 0 || tag_len > W::BLOCK_BYTES {
+            return Err(Error::WrongTagSize);
+        }
+        Ok(Self {
+            tag_len,
+            ..Self::new(cipher)
+        })
+    }
+
+    /// Add `input` to the authenticated message
+    pub fn update(&mut self, mut input: &[u8]) {
+        while !input.is_empty() {
+            if self.pending.len() == W::BLOCK_BYTES {
+                xor_bytes(&mut self.state, &self.pending);
+                encode_bytes(&self.cipher, &mut self.state);
+                self.pending.clear();
+            }
+            let take = (W::BLOCK_BYTES - self.pending.len()).min(input.len());
+            self.pending.extend_from_slice(&input[..take]);
+            input = &input[take..];
+        }
+    }
+
+    /// The tag of the whole message
+    pub fn finalize(mut self) -> Vec<u8> {
+        let subkey = match self.pending.len() == W::BLOCK_BYTES {
+            true => &self.subkeys.0,
+            false => {
+                self.pending.push(0x80);
+                self.pending.resize(W::BLOCK_BYTES, 0x00);
+                &self.subkeys.1
+            }
+        };
+        xor_bytes(&mut self.pending, subkey);
+        xor_bytes(&mut self.state, &self.pending);
+        encode_bytes(&self.cipher, &mut self.state);
+        self.state.truncate(self.tag_len);
+        self.state
+    }
+
+    /// Check `tag` against the tag of the whole message in constant time
+    ///
+    /// `Error::AuthenticationFailed` - if the tag doesn't match
+    pub fn verify(self, tag: &[u8]) -> Result<(), Error> {
+        match constant_time_eq(&self.finalize(), tag) {
+            true => Ok(()),
+            false => Err(Error::AuthenticationFailed),
+        }
+    }
+}
+
+/// `K1` and `K2` of `cipher`
+pub(crate) fn cmac_subkeys<W: Word>(cipher: &impl EncodeBlock<W>) -> (Vec<u8>, Vec<u8>) {
+    let mut k1 = vec![0x00; W::BLOCK_BYTES];
+    encode_bytes(cipher, &mut k1);
+    double(&mut k1);
+    let mut k2 = k1.clone();
+    double(&mut k2);
+    (k1, k2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schedule::KeySchedule, settings::Rc5Settings};
+
+    fn cmac<W: Word>(schedule: &KeySchedule<W>, input: &[u8]) -> Vec<u8> {
+        let mut cmac = Cmac::new(schedule);
+        cmac.update(input);
+        cmac.finalize()
+    }
+
+    #[test]
+    fn test_vectors() {
+        let key: [u8; 16] = (0..16).collect::<Vec<_>>().try_into().unwrap();
+        let schedule = KeySchedule::new(key, Rc5Settings::default());
+        assert_eq!(hex::encode(cmac(&schedule, &[])), "06c002bdb08b3c68");
+        let input = (0..20).collect::<Vec<u8>>();
+        assert_eq!(hex::encode(cmac(&schedule, &input)), "d2fdb76a83b991d6");
+        assert_eq!(
+            hex::encode(cmac(&schedule, &input[..16])),
+            "8de82dbbce313029"
+        );
+
+        let schedule = KeySchedule::new(key, Rc5Settings::<u64>::new(16));
+        let input = (0..40).collect::<Vec<u8>>();
+        assert_eq!(
+            hex::encode(cmac(&schedule, &input)),
+            "55ac57e2402fe34c8df8ea91495bd736"
+        );
+
+        let schedule = KeySchedule::new(key, Rc5Settings::<u128>::new(20));
+        let input = (0..70).collect::<Vec<u8>>();
+        assert_eq!(
+            hex::encode(cmac(&schedule, &input)),
+            "7a6e1fa2ee77108d55e61d9e497178ac241b4158b537aac15cc17a673070fa93"
+        );
+
+        let schedule = KeySchedule::new([0x00, 0x01, 0x02, 0x03], Rc5Settings::<u16>::new(12));
+        assert_eq!(hex::encode(cmac(&schedule, &input[..8])), "50658607");
+        let schedule = KeySchedule::new([0x00, 0x01, 0x02, 0x03], Rc5Settings::<u8>::new(12));
+        assert_eq!(hex::encode(cmac(&schedule, &input[..5])), "7e61");
+    }
+
+    #[test]
+    fn test_truncated_tag() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u64>::new(16));
+        let tag = cmac(&schedule, b"firmware image");
+        for tag_len in 1..=16 {
+            let mut cmac = Cmac::with_tag_len(&schedule, tag_len).unwrap();
+            cmac.update(b"firmware image");
+            assert_eq!(cmac.finalize(), tag[..tag_len]);
+        }
+        assert!(matches!(
+            Cmac::with_tag_len(&schedule, 0),
+            Err(Error::WrongTagSize)
+        ));
+        assert!(matches!(
+            Cmac::with_tag_len(&schedule, 17),
+            Err(Error::WrongTagSize)
+        ));
+    }
+
+    #[test]
+    fn test_verify() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::default());
+        let tag = cmac(&schedule, b"firmware image");
+
+        let mut cmac = Cmac::new(&schedule);
+        cmac.update(b"firmware image");
+        assert_eq!(cmac.verify(&tag), Ok(()));
+
+        let mut cmac = Cmac::new(&schedule);
+        cmac.update(b"firmware imagE");
+        assert_eq!(cmac.verify(&tag), Err(Error::AuthenticationFailed));
+
+        // A truncated tag is checked only against a truncated CMAC
+        let mut cmac = Cmac::new(&schedule);
+        cmac.update(b"firmware image");
+        assert_eq!(cmac.verify(&tag[..4]), Err(Error::AuthenticationFailed));
+        let mut cmac = Cmac::with_tag_len(&schedule, 4).unwrap();
+        cmac.update(b"firmware image");
+        assert_eq!(cmac.verify(&tag[..4]), Ok(()));
+    }
+
+    #[test]
+    fn test_incremental() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u16>::new(12));
+        let input = (0..50).collect::<Vec<u8>>();
+        let tag = cmac(&schedule, &input);
+        for step in [1, 3, 4, 7] {
+            let mut cmac = Cmac::new(&schedule);
+            input.chunks(step).for_each(|chunk| cmac.update(chunk));
+            assert_eq!(cmac.finalize(), tag, "step {step}");
+        }
+    }
+}
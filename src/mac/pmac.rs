@@ -0,0 +1,287 @@
+use std::marker::PhantomData;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{
+    block::xor_bytes,
+    cipher::EncodeBlock,
+    mac::{constant_time_eq, double, encode_bytes, halve},
+    word::Word,
+    Error,
+};
+
+/// PMAC1 from [the paper of Rogaway](https://web.cs.ucdavis.edu/~rogaway/ocb/pmac.pdf)
+/// over a block of any size
+///
+/// Unlike [`crate::Cmac`], every block but the last one is encoded independently
+/// with an offset derived from its index, so the blocks can be processed in any order.
+/// With the `rayon` feature [`Pmac::par_update`] spreads them across threads
+/// and gives the same tag as [`Pmac::update`]
+pub struct Pmac<W: Word, C: EncodeBlock<W>> {
+    cipher: C,
+    tag_len: usize,
+    /// `L(i) = L * x^i`, one for every possible count of trailing zeros of a block index
+    l: Vec<Vec<u8>>,
+    /// `L(-1) = L / x`, for the complete last block
+    l_inverse: Vec<u8>,
+    sum: Vec<u8>,
+    /// Count of blocks already added to `sum`
+    blocks: usize,
+    /// The last block is kept until finalization, because it's processed differently
+    pending: Vec<u8>,
+    _p: PhantomData<W>,
+}
+
+impl<W: Word, C: EncodeBlock<W>> Pmac<W, C> {
+    /// PMAC with the full-block tag
+    pub fn new(cipher: C) -> Self {
+        let mut block = vec![0x00; W::BLOCK_BYTES];
+        encode_bytes(&cipher, &mut block);
+        let mut l_inverse = block.clone();
+        halve(&mut l_inverse);
+        let l = (0..usize::BITS)
+            .map(|_| {
+                let l = block.clone();
+                double(&mut block);
+                l
+            })
+            .collect();
+
+        Self {
+            cipher,
+            tag_len: W::BLOCK_BYTES,
+            l,
+            l_inverse,
+            sum: vec![0x00; W::BLOCK_BYTES],
+            blocks: 0,
+            pending: Vec::with_capacity(W::BLOCK_BYTES),
+            _p: PhantomData::default(),
+        }
+    }
+
+    /// PMAC with the tag truncated to `tag_len` bytes
+    ///
+    /// `Error` - if `tag_len` is zero or more than the block size
+    pub fn with_tag_len(cipher: C, tag_len: usize) -> Result<Self, Error> {
+        if tag_len == 0 || tag_len > W::BLOCK_BYTES {
+            return Err(Error::WrongTagSize);
+        }
+        Ok(Self {
+            tag_len,
+            ..Self::new(cipher)
+        })
+    }
+
+    /// Add `input` to the authenticated message
+    pub fn update(&mut self, input: &[u8]) {
+        let blocks = self.buffer(input);
+        let (start, l, cipher) = (self.blocks, &self.l, &self.cipher);
+        let sum = blocks
+            .chunks(W::BLOCK_BYTES)
+            .enumerate()
+            .map(|(index, block)| encode_with_offset(cipher, l, start + index + 1, block))
+            .fold(vec![0x00; W::BLOCK_BYTES], xor);
+        self.add(&sum, blocks.len());
+    }
+
+    /// The tag of the whole message
+    pub fn finalize(mut self) -> Vec<u8> {
+        match self.pending.len() == W::BLOCK_BYTES {
+            true => xor_bytes(&mut self.pending, &self.l_inverse),
+            false => {
+                self.pending.push(0x80);
+                self.pending.resize(W::BLOCK_BYTES, 0x00);
+            }
+        }
+        xor_bytes(&mut self.sum, &self.pending);
+        encode_bytes(&self.cipher, &mut self.sum);
+        self.sum.truncate(self.tag_len);
+        self.sum
+    }
+
+    /// Check `tag` against the tag of the whole message in constant time
+    ///
+    /// `Error::AuthenticationFailed` - if the tag doesn't match
+    pub fn verify(self, tag: &[u8]) -> Result<(), Error> {
+        match constant_time_eq(&self.finalize(), tag) {
+            true => Ok(()),
+            false => Err(Error::AuthenticationFailed),
+        }
+    }
+
+    /// Fill the pending block from `input`. If there is more input after it,
+    /// the pending block is not the last one: it's added right away
+    /// and the last block of `input` becomes pending.
+    ///
+    /// Returns the full blocks of `input` between them
+    fn buffer<'i>(&mut self, input: &'i [u8]) -> &'i [u8] {
+        let take = (W::BLOCK_BYTES - self.pending.len()).min(input.len());
+        self.pending.extend_from_slice(&input[..take]);
+        let input = &input[take..];
+        if input.is_empty() {
+            return input;
+        }
+
+        let sum = encode_with_offset(&self.cipher, &self.l, self.blocks + 1, &self.pending);
+        self.add(&sum, W::BLOCK_BYTES);
+
+        let last_len = match input.len() % W::BLOCK_BYTES {
+            0 => W::BLOCK_BYTES,
+            rest => rest,
+        };
+        let (blocks, last) = input.split_at(input.len() - last_len);
+        self.pending.clear();
+        self.pending.extend_from_slice(last);
+        blocks
+    }
+
+    fn add(&mut self, sum: &[u8], len: usize) {
+        xor_bytes(&mut self.sum, sum);
+        self.blocks += len / W::BLOCK_BYTES;
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<W: Word, C: EncodeBlock<W> + Sync> Pmac<W, C> {
+    /// Same as [`Pmac::update`], but the blocks are encoded in parallel
+    pub fn par_update(&mut self, input: &[u8]) {
+        let blocks = self.buffer(input);
+        let (start, l, cipher) = (self.blocks, &self.l, &self.cipher);
+        let sum = blocks
+            .par_chunks(W::BLOCK_BYTES)
+            .enumerate()
+            .map(|(index, block)| encode_with_offset(cipher, l, start + index + 1, block))
+            .reduce(|| vec![0x00; W::BLOCK_BYTES], xor);
+        self.add(&sum, blocks.len());
+    }
+}
+
+/// `E(block ^ Offset(index))`, where the offset is the XOR of `L(j)`
+/// for every bit `j` of the Gray code of `index`
+fn encode_with_offset<W: Word>(
+    cipher: &impl EncodeBlock<W>,
+    l: &[Vec<u8>],
+    index: usize,
+    block: &[u8],
+) -> Vec<u8> {
+    let gray = index ^ (index >> 1);
+    let mut output = block.to_vec();
+    l.iter()
+        .enumerate()
+        .filter(|(bit, _)| (gray >> bit) & 1 == 1)
+        .for_each(|(_, l)| xor_bytes(&mut output, l));
+    encode_bytes(cipher, &mut output);
+    output
+}
+
+fn xor(mut lhs: Vec<u8>, rhs: Vec<u8>) -> Vec<u8> {
+    xor_bytes(&mut lhs, &rhs);
+    lhs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schedule::KeySchedule, settings::Rc5Settings};
+
+    fn pmac<W: Word>(schedule: &KeySchedule<W>, input: &[u8]) -> Vec<u8> {
+        let mut pmac = Pmac::new(schedule);
+        pmac.update(input);
+        pmac.finalize()
+    }
+
+    #[test]
+    fn test_vectors() {
+        let key: [u8; 16] = (0..16).collect::<Vec<_>>().try_into().unwrap();
+        let input = (0..100).collect::<Vec<u8>>();
+
+        let schedule = KeySchedule::new(key, Rc5Settings::default());
+        for (len, tag) in [
+            (0, "d874fd706f300a7e"),
+            (3, "5d294978844c99c2"),
+            (8, "56e9128042dd1a46"),
+            (20, "97b52c0db9a0e472"),
+        ] {
+            assert_eq!(hex::encode(pmac(&schedule, &input[..len])), tag, "{len}");
+        }
+
+        let schedule = KeySchedule::new(key, Rc5Settings::<u64>::new(16));
+        assert_eq!(
+            hex::encode(pmac(&schedule, &input[..16])),
+            "d21d29a81146198eb3335ef415d67468"
+        );
+        assert_eq!(
+            hex::encode(pmac(&schedule, &input)),
+            "c479c1a9801bd482d50896b93c954bba"
+        );
+
+        let schedule = KeySchedule::new(key, Rc5Settings::<u128>::new(20));
+        assert_eq!(
+            hex::encode(pmac(&schedule, &input[..70])),
+            "8dbc8c7039db7a9abe8b1197a4daafdc87038593287c35ee545b3a87e42ec819"
+        );
+
+        let schedule = KeySchedule::new([0x00, 0x01, 0x02, 0x03], Rc5Settings::<u16>::new(12));
+        assert_eq!(hex::encode(pmac(&schedule, &input[..12])), "5e1c9be2");
+        let schedule = KeySchedule::new([0x00, 0x01, 0x02, 0x03], Rc5Settings::<u8>::new(12));
+        assert_eq!(hex::encode(pmac(&schedule, &input[..7])), "1a81");
+    }
+
+    #[test]
+    fn test_incremental() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u16>::new(12));
+        let input = (0..50).collect::<Vec<u8>>();
+        let tag = pmac(&schedule, &input);
+        for step in [1, 3, 4, 7, 8, 9] {
+            let mut pmac = Pmac::new(&schedule);
+            input.chunks(step).for_each(|chunk| pmac.update(chunk));
+            pmac.update(&[]);
+            assert_eq!(pmac.finalize(), tag, "step {step}");
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u64>::new(16));
+        let input = (0..10_000).map(|byte| byte as u8).collect::<Vec<u8>>();
+        for len in [0, 1, 16, 17, 160, 9_999, 10_000] {
+            let tag = pmac(&schedule, &input[..len]);
+
+            let mut pmac = Pmac::new(&schedule);
+            pmac.par_update(&input[..len]);
+            assert_eq!(pmac.finalize(), tag, "{len}");
+
+            // Mixed with the serial path
+            let mut pmac = Pmac::new(&schedule);
+            input[..len]
+                .chunks(1000)
+                .enumerate()
+                .for_each(|(index, chunk)| match index % 2 {
+                    0 => pmac.par_update(chunk),
+                    _ => pmac.update(chunk),
+                });
+            assert_eq!(pmac.finalize(), tag, "{len}");
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::default());
+        let tag = pmac(&schedule, b"large object");
+
+        let mut pmac = Pmac::with_tag_len(&schedule, 4).unwrap();
+        pmac.update(b"large object");
+        assert_eq!(pmac.verify(&tag[..4]), Ok(()));
+
+        let mut pmac = Pmac::new(&schedule);
+        pmac.update(b"large objecT");
+        assert_eq!(pmac.verify(&tag), Err(Error::AuthenticationFailed));
+
+        assert!(matches!(
+            Pmac::with_tag_len(&schedule, 9),
+            Err(Error::WrongTagSize)
+        ));
+    }
+}
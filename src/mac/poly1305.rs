@@ -0,0 +1,223 @@
+use crate::{
+    cipher::EncodeBlock,
+    mac::{constant_time_eq, encode_bytes},
+    Error,
+};
+
+const LIMB_MASK: u32 = 0x3ffffff;
+
+/// Poly1305-RC5: [Poly1305-AES](https://cr.yp.to/mac/poly1305-20050329.pdf)
+/// with RC5 on `u64` words (a 128-bit block) as the nonce cipher
+///
+/// The key is a pair: the cipher and the 16-byte polynomial key `r`.
+/// The tag is `(poly_r(message) + E(nonce)) mod 2^128`, so the nonce
+/// must never repeat with the same key: two tags under one nonce differ
+/// by a value that doesn't depend on the cipher key, which leaks `r` and allows forgeries
+pub struct Poly1305<C: EncodeBlock<u64>> {
+    cipher: C,
+    /// Clamped `r` in 26-bit limbs
+    r: [u32; 5],
+}
+
+impl<C: EncodeBlock<u64>> Poly1305<C> {
+    /// `r` is clamped as the specification requires
+    pub fn new(cipher: C, r: [u8; 16]) -> Self {
+        Self {
+            cipher,
+            r: [
+                le_u32(&r[0..4]) & 0x3ffffff,
+                (le_u32(&r[3..7]) >> 2) & 0x3ffff03,
+                (le_u32(&r[6..10]) >> 4) & 0x3ffc0ff,
+                (le_u32(&r[9..13]) >> 6) & 0x3f03fff,
+                (le_u32(&r[12..16]) >> 8) & 0x00fffff,
+            ],
+        }
+    }
+
+    /// Tag of the `message` under the `nonce`
+    pub fn compute(&self, nonce: &[u8; 16], message: &[u8]) -> [u8; 16] {
+        let mut pad = *nonce;
+        encode_bytes(&self.cipher, &mut pad);
+
+        let h = self.polynomial(message);
+        let mut tag = [0x00; 16];
+        let mut carry = 0u64;
+        for (index, word) in h.iter().enumerate() {
+            carry += u64::from(*word) + u64::from(le_u32(&pad[4 * index..4 * index + 4]));
+            tag[4 * index..4 * index + 4].copy_from_slice(&(carry as u32).to_le_bytes());
+            carry >>= 32;
+        }
+        tag
+    }
+
+    /// Check the tag of the `message` in constant time
+    ///
+    /// `Error::AuthenticationFailed` - if the tag doesn't match
+    pub fn verify(&self, nonce: &[u8; 16], message: &[u8], tag: &[u8]) -> Result<(), Error> {
+        match constant_time_eq(&self.compute(nonce, message), tag) {
+            true => Ok(()),
+            false => Err(Error::AuthenticationFailed),
+        }
+    }
+
+    /// `poly_r(message) mod 2^130 - 5` as four 32-bit words, the top bits dropped
+    ///
+    /// Limbs and masks instead of branches, so the time depends only on the length
+    fn polynomial(&self, message: &[u8]) -> [u32; 4] {
+        let [r0, r1, r2, r3, r4] = self.r.map(u64::from);
+        let [s1, s2, s3, s4] = [r1 * 5, r2 * 5, r3 * 5, r4 * 5];
+        let mut h = [0u32; 5];
+
+        for chunk in message.chunks(16) {
+            let mut block = [0x00; 17];
+            block[..chunk.len()].copy_from_slice(chunk);
+            block[chunk.len()] = 0x01;
+
+            h[0] += le_u32(&block[0..4]) & LIMB_MASK;
+            h[1] += (le_u32(&block[3..7]) >> 2) & LIMB_MASK;
+            h[2] += (le_u32(&block[6..10]) >> 4) & LIMB_MASK;
+            h[3] += (le_u32(&block[9..13]) >> 6) & LIMB_MASK;
+            h[4] += (le_u32(&block[12..16]) >> 8) | (u32::from(block[16]) << 24);
+
+            let [h0, h1, h2, h3, h4] = h.map(u64::from);
+            let mut d = [
+                h0 * r0 + h1 * s4 + h2 * s3 + h3 * s2 + h4 * s1,
+                h0 * r1 + h1 * r0 + h2 * s4 + h3 * s3 + h4 * s2,
+                h0 * r2 + h1 * r1 + h2 * r0 + h3 * s4 + h4 * s3,
+                h0 * r3 + h1 * r2 + h2 * r1 + h3 * r0 + h4 * s4,
+                h0 * r4 + h1 * r3 + h2 * r2 + h3 * r1 + h4 * r0,
+            ];
+            for index in 0..4 {
+                d[index + 1] += d[index] >> 26;
+                h[index] = d[index] as u32 & LIMB_MASK;
+            }
+            h[4] = d[4] as u32 & LIMB_MASK;
+            h[0] += (d[4] >> 26) as u32 * 5;
+            h[1] += h[0] >> 26;
+            h[0] &= LIMB_MASK;
+        }
+
+        // Full carry, then `h - p` is taken if it doesn't underflow
+        for index in 1..5 {
+            h[index] += h[index - 1] >> 26;
+            h[index - 1] &= LIMB_MASK;
+        }
+        h[0] += (h[4] >> 26) * 5;
+        h[4] &= LIMB_MASK;
+        h[1] += h[0] >> 26;
+        h[0] &= LIMB_MASK;
+
+        let mut g = [0u32; 5];
+        let mut carry = 5;
+        for index in 0..5 {
+            g[index] = h[index] + carry;
+            carry = g[index] >> 26;
+            g[index] &= LIMB_MASK;
+        }
+        g[4] = g[4].wrapping_add(carry << 26).wrapping_sub(1 << 26);
+        let use_g = (g[4] >> 31).wrapping_sub(1);
+        for index in 0..5 {
+            h[index] = (h[index] & !use_g) | (g[index] & use_g);
+        }
+
+        [
+            h[0] | (h[1] << 26),
+            (h[1] >> 6) | (h[2] << 20),
+            (h[2] >> 12) | (h[3] << 14),
+            (h[3] >> 18) | (h[4] << 8),
+        ]
+    }
+}
+
+fn le_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes(bytes.try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schedule::KeySchedule, settings::Rc5Settings};
+
+    fn schedule(key: [u8; 16]) -> KeySchedule<u64> {
+        KeySchedule::new(key, Rc5Settings::new(16))
+    }
+
+    #[test]
+    fn test_vectors() {
+        let key: [u8; 16] = (0x00..0x10).collect::<Vec<_>>().try_into().unwrap();
+        let r: [u8; 16] = (0x40..0x50).collect::<Vec<_>>().try_into().unwrap();
+        let nonce: [u8; 16] = (0xa0..0xb0).collect::<Vec<_>>().try_into().unwrap();
+        let schedule = schedule(key);
+        let poly1305 = Poly1305::new(&schedule, r);
+
+        let input = (0..100).collect::<Vec<u8>>();
+        for (len, tag) in [
+            (0, "bde9512cd68bb9866df03f7e28471470"),
+            (1, "cc29936ed9cffecc743889c8339361be"),
+            (15, "79068adf4b5ea3e68962c16cc9320337"),
+            (16, "7ddfd5099d823a5c26d2a32db1ed30cb"),
+            (17, "3e9bb6ae03f4d16b7085e80fc0461308"),
+            (32, "90dd6ca001b8f5b5b0741f54e0faf2d7"),
+            (100, "7bec87f3e80e89ad4b21d981dedc1f8b"),
+        ] {
+            assert_eq!(
+                hex::encode(poly1305.compute(&nonce, &input[..len])),
+                tag,
+                "{len}"
+            );
+        }
+
+        // Big limbs, to check the carries
+        let poly1305 = Poly1305::new(&schedule, [0xff; 16]);
+        assert_eq!(
+            hex::encode(poly1305.compute(&nonce, &[0xff; 64])),
+            "4ef9345897eb615e2a992f63f02a9321"
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        let schedule = schedule([0x42; 16]);
+        let poly1305 = Poly1305::new(&schedule, [0x24; 16]);
+        let tag = poly1305.compute(&[0x01; 16], b"message");
+
+        assert_eq!(poly1305.verify(&[0x01; 16], b"message", &tag), Ok(()));
+        assert_eq!(
+            poly1305.verify(&[0x01; 16], b"Message", &tag),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(
+            poly1305.verify(&[0x02; 16], b"message", &tag),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(
+            poly1305.verify(&[0x01; 16], b"message", &tag[..8]),
+            Err(Error::AuthenticationFailed)
+        );
+    }
+
+    /// Documents the misuse: under a reused nonce the difference of two tags
+    /// is `poly_r(m1) - poly_r(m2)`, the cipher key doesn't affect it
+    #[test]
+    fn test_reused_nonce() {
+        let r = [0x24; 16];
+        let nonce = [0x01; 16];
+        let difference = |schedule: &KeySchedule<u64>| {
+            let poly1305 = Poly1305::new(schedule, r);
+            let first = u128::from_le_bytes(poly1305.compute(&nonce, b"first message"));
+            let second = u128::from_le_bytes(poly1305.compute(&nonce, b"second message"));
+            first.wrapping_sub(second)
+        };
+
+        let difference_under_one_key = difference(&schedule([0x42; 16]));
+        let difference_under_other_key = difference(&schedule([0x43; 16]));
+        assert_eq!(difference_under_one_key, difference_under_other_key);
+
+        // Distinct nonces hide it
+        let schedule = schedule([0x42; 16]);
+        let poly1305 = Poly1305::new(&schedule, r);
+        let first = u128::from_le_bytes(poly1305.compute(&[0x01; 16], b"first message"));
+        let second = u128::from_le_bytes(poly1305.compute(&[0x02; 16], b"second message"));
+        assert_ne!(first.wrapping_sub(second), difference_under_one_key);
+    }
+}
@@ -0,0 +1,152 @@
+use std::{collections::HashSet, marker::PhantomData};
+
+use crate::{block::read_block, word::Word, Error};
+
+mod block_mode;
+use block_mode::process_buffered;
+pub use block_mode::{BlockMode, Direction, ModeIv, ModeStream, StreamMode};
+
+/// Conformance checks shared by all modes, built-in and your own ones:
+/// call them from tests, they panic on a failure.
+/// Available with the `test-util` feature
+#[cfg(any(test, feature = "test-util"))]
+pub mod conformance;
+
+mod ecb;
+pub use ecb::{EcbDecoder, EcbEncoder};
+
+mod cbc;
+pub use cbc::{CbcDecoder, CbcEncoder};
+
+mod cbc_pad;
+pub use cbc_pad::{CbcPadDecoder, CbcPadEncoder};
+
+mod cts;
+pub use cts::{CtsDecoder, CtsEncoder, CtsVariant};
+
+mod ctr;
+pub use ctr::Ctr;
+
+mod cfb;
+pub use cfb::{CfbDecoder, CfbEncoder};
+
+mod ofb;
+pub use ofb::Ofb;
+
+mod pcbc;
+pub use pcbc::{PcbcDecoder, PcbcEncoder};
+
+mod ige;
+pub use ige::{IgeDecoder, IgeEncoder};
+
+/// Load the initialization vector as a block
+///
+/// `Error` - if `iv` is not exactly one block (pair of words) long
+pub(crate) fn read_iv<W: Word>(iv: &[u8]) -> Result<(W, W), Error> {
+    if iv.len() != W::BLOCK_BYTES {
+        return Err(Error::WrongIvSize);
+    }
+    Ok(read_block(iv))
+}
+
+/// Opt-in check that initialization vectors are not reused
+///
+/// Remembers every IV it's shown, so keep one guard per key
+/// and only for as long as the key is in use
+#[derive(Debug, Default)]
+pub struct IvGuard {
+    seen: HashSet<Vec<u8>>,
+}
+
+impl IvGuard {
+    /// Register `iv` as used
+    ///
+    /// `Error::IvReused` - if `iv` was registered before
+    pub fn check(&mut self, iv: &[u8]) -> Result<(), Error> {
+        match self.seen.insert(iv.to_vec()) {
+            true => Ok(()),
+            false => Err(Error::IvReused),
+        }
+    }
+}
+
+/// Collects the input of the streaming modes
+/// until there are enough bytes for complete blocks
+pub(crate) struct BlockBuffer<W: Word> {
+    pending: Vec<u8>,
+    _p: PhantomData<W>,
+}
+
+impl<W: Word> Default for BlockBuffer<W> {
+    fn default() -> Self {
+        Self {
+            pending: Vec::with_capacity(W::BLOCK_BYTES),
+            _p: PhantomData::default(),
+        }
+    }
+}
+
+impl<W: Word> BlockBuffer<W> {
+    /// Append `input` and take out all complete blocks,
+    /// except the ones that overlap the last `hold` bytes.
+    /// Modes that have to treat the final blocks differently
+    /// (padding, ciphertext stealing) keep them this way until finalization
+    pub(crate) fn push(&mut self, input: &[u8], hold: usize) -> Vec<(W, W)> {
+        self.pending.extend_from_slice(input);
+        let ready = self.pending.len().saturating_sub(hold) / W::BLOCK_BYTES * W::BLOCK_BYTES;
+        let blocks = self.pending[..ready]
+            .chunks(W::BLOCK_BYTES)
+            .map(read_block)
+            .collect();
+        self.pending.drain(..ready);
+        blocks
+    }
+
+    /// Bytes that are not yet taken out by [`BlockBuffer::push`]
+    pub(crate) fn pending(&self) -> &[u8] {
+        &self.pending
+    }
+
+    /// Take out all bytes left, including incomplete blocks
+    pub(crate) fn take(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_iv() {
+        assert_eq!(read_iv::<u16>(&[0x01, 0x00, 0x02, 0x00]), Ok((1, 2)));
+        assert_eq!(read_iv::<u16>(&[0x01, 0x00, 0x02]), Err(Error::WrongIvSize));
+        assert_eq!(
+            read_iv::<u16>(&[0x01, 0x00, 0x02, 0x00, 0x03]),
+            Err(Error::WrongIvSize)
+        );
+    }
+
+    #[test]
+    fn test_iv_guard() {
+        let mut guard = IvGuard::default();
+        assert_eq!(guard.check(&[0x01, 0x02]), Ok(()));
+        assert_eq!(guard.check(&[0x02, 0x01]), Ok(()));
+        assert_eq!(guard.check(&[0x01, 0x02]), Err(Error::IvReused));
+    }
+
+    #[test]
+    fn test_block_buffer() {
+        let mut buffer = BlockBuffer::<u8>::default();
+        assert_eq!(buffer.push(&[0x01], 0), []);
+        assert_eq!(buffer.push(&[0x02, 0x03, 0x04, 0x05], 0), [(1, 2), (3, 4)]);
+        assert_eq!(buffer.pending(), [0x05]);
+
+        assert_eq!(buffer.push(&[0x06, 0x07, 0x08], 2), [(5, 6)]);
+        assert_eq!(buffer.pending(), [0x07, 0x08]);
+        assert_eq!(buffer.push(&[], 3), []);
+        assert_eq!(buffer.pending(), [0x07, 0x08]);
+        assert_eq!(buffer.take(), [0x07, 0x08]);
+        assert_eq!(buffer.pending(), []);
+    }
+}
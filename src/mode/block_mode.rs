@@ -0,0 +1,343 @@
+use crate::{
+    block::{append_block, read_block},
+    mode::BlockBuffer,
+    padding::Padding,
+    word::Word,
+    Error,
+};
+
+/// Side of a [`BlockMode`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Encode,
+    Decode,
+}
+
+/// Initialization vector of a [`BlockMode`]: an array of blocks,
+/// `[(W, W); 0]` for modes without it
+pub trait ModeIv<W: Word>: Sized {
+    /// Count of blocks in the IV
+    const BLOCKS: usize;
+
+    /// Load the IV from bytes
+    ///
+    /// `Error` - if `iv` is not exactly [`ModeIv::BLOCKS`] long
+    fn from_bytes(iv: &[u8]) -> Result<Self, Error>;
+}
+
+impl<W: Word, const N: usize> ModeIv<W> for [(W, W); N] {
+    const BLOCKS: usize = N;
+
+    fn from_bytes(iv: &[u8]) -> Result<Self, Error> {
+        if iv.len() != N * W::BLOCK_BYTES {
+            return Err(Error::WrongIvSize);
+        }
+        Ok(std::array::from_fn(|index| {
+            read_block(&iv[index * W::BLOCK_BYTES..])
+        }))
+    }
+}
+
+/// Block cipher mode of operation that works on whole blocks
+///
+/// Only the chaining of blocks is up to the mode: it keeps nothing but
+/// the chaining state. [`ModeStream`] drives it: reads the IV,
+/// buffers partial blocks and handles the padding. Built-in modes
+/// ([`crate::CbcEncoder`], [`crate::IgeDecoder`], ...) implement it
+/// and so can your own ones:
+/// ```
+/// use rc5_cypher::*;
+///
+/// /// Every block is XORed with the IV before encoding
+/// struct Whitened<C> {
+///     cipher: C,
+///     iv: (u32, u32),
+/// }
+///
+/// impl<C: EncodeBlock<u32>> BlockMode<u32> for Whitened<C> {
+///     type Cipher = C;
+///     type Iv = [(u32, u32); 1];
+///     const DIRECTION: Direction = Direction::Encode;
+///
+///     fn init(cipher: C, [iv]: [(u32, u32); 1]) -> Self {
+///         Self { cipher, iv }
+///     }
+///
+///     fn process_block(&mut self, (a, b): (u32, u32)) -> (u32, u32) {
+///         self.cipher.encode_block((a ^ self.iv.0, b ^ self.iv.1))
+///     }
+/// }
+///
+/// let schedule = KeySchedule::new([0x00; 16], Rc5Settings::default());
+/// let mut stream = ModeStream::<_, Whitened<_>>::init_padded(&schedule, &[0x01; 8], Pkcs7).unwrap();
+/// let mut ct = stream.update(b"any ");
+/// ct.extend(stream.update(b"length"));
+/// ct.extend(stream.finalize().unwrap());
+/// assert_eq!(ct.len(), 16);
+/// ```
+pub trait BlockMode<W: Word>: Sized {
+    /// Keyed cipher the mode needs:
+    /// usually [`crate::EncodeBlock`] or [`crate::DecodeBlock`]
+    type Cipher;
+
+    /// Initialization vector: `[(W, W); N]` of `N` blocks
+    type Iv: ModeIv<W>;
+
+    /// Encryption or decryption side of the mode,
+    /// sets which side of the padding [`ModeStream`] handles
+    const DIRECTION: Direction;
+
+    /// Start the mode from the IV
+    fn init(cipher: Self::Cipher, iv: Self::Iv) -> Self;
+
+    /// Process the next block of the message
+    fn process_block(&mut self, block: (W, W)) -> (W, W);
+}
+
+/// Streaming driver of a [`BlockMode`]
+///
+/// [`ModeStream::update`] processes all complete blocks available so far,
+/// [`ModeStream::finalize`] handles the rest: pads it on the encryption side
+/// and removes the padding on the decryption side. Without padding
+/// the total input must be a multiple of the block size
+pub struct ModeStream<W: Word, M: BlockMode<W>> {
+    mode: M,
+    buffer: BlockBuffer<W>,
+    padding: Option<Box<dyn Padding>>,
+}
+
+impl<W: Word, M: BlockMode<W>> ModeStream<W, M> {
+    /// Stream of the mode started from the loaded `iv`
+    pub fn new(cipher: M::Cipher, iv: M::Iv) -> Self {
+        Self {
+            mode: M::init(cipher, iv),
+            buffer: BlockBuffer::default(),
+            padding: None,
+        }
+    }
+
+    /// Same as [`ModeStream::new`], but the IV is loaded from bytes
+    ///
+    /// `Error` - if `iv` is not exactly [`ModeIv::BLOCKS`] long
+    pub fn init(cipher: M::Cipher, iv: &[u8]) -> Result<Self, Error> {
+        Ok(Self::new(cipher, M::Iv::from_bytes(iv)?))
+    }
+
+    /// Same as [`ModeStream::init`], but the message is padded with `padding`
+    pub fn init_padded(
+        cipher: M::Cipher,
+        iv: &[u8],
+        padding: impl Padding + 'static,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            padding: Some(Box::new(padding)),
+            ..Self::init(cipher, iv)?
+        })
+    }
+
+    /// Process all complete blocks available so far, the rest is kept until the next call.
+    /// The padded decryption side also keeps the last block, because it contains the padding
+    pub fn update(&mut self, input: &[u8]) -> Vec<u8> {
+        let hold = match (&self.padding, M::DIRECTION) {
+            (Some(_), Direction::Decode) => W::BLOCK_BYTES,
+            _ => 0,
+        };
+        self.process(input, hold)
+    }
+
+    /// Process the rest of the message
+    ///
+    /// `Error::WrongInputSize` - if the total input cannot be divided into blocks
+    /// and the encryption side isn't padded
+    /// `Error::WrongPadding` - if the decoded padding is malformed
+    pub fn finalize(mut self) -> Result<Vec<u8>, Error> {
+        match (self.padding.take(), M::DIRECTION) {
+            (Some(mut padding), Direction::Encode) => {
+                let padding = padding.padding(W::BLOCK_BYTES, self.buffer.pending().len());
+                Ok(self.process(&padding, 0))
+            }
+            (Some(padding), Direction::Decode) => {
+                if self.buffer.pending().len() % W::BLOCK_BYTES != 0 {
+                    return Err(Error::WrongInputSize);
+                }
+                let mut last_block = self.process(&[], 0);
+                let data_len = padding.data_len(&last_block)?;
+                last_block.truncate(data_len);
+                Ok(last_block)
+            }
+            (None, _) => match self.buffer.pending().is_empty() {
+                true => Ok(vec![]),
+                false => Err(Error::WrongInputSize),
+            },
+        }
+    }
+
+    /// One-shot processing of the whole `input`
+    ///
+    /// Check [`ModeStream::finalize`] for errors
+    pub fn process_all(mut self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut output = self.update(input);
+        output.extend(self.finalize()?);
+        Ok(output)
+    }
+
+    fn process(&mut self, input: &[u8], hold: usize) -> Vec<u8> {
+        process_buffered(&mut self.mode, &mut self.buffer, input, hold)
+    }
+}
+
+/// Incremental interface shared by all modes
+///
+/// Implemented by [`ModeStream`] of any [`BlockMode`], by the modes that hold
+/// more than one block back (CBC-Pad, CTS) and by the stream modes (CFB, OFB, CTR),
+/// so generic code and the conformance checks can drive any of them
+pub trait StreamMode: Sized {
+    /// Process the next chunk of the message, the output may lag behind the input
+    fn update(&mut self, input: &[u8]) -> Result<Vec<u8>, Error>;
+
+    /// Process the rest of the message
+    fn finalize(self) -> Result<Vec<u8>, Error>;
+
+    /// One-shot processing of the whole `input`
+    fn process_all(mut self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut output = self.update(input)?;
+        output.extend(self.finalize()?);
+        Ok(output)
+    }
+}
+
+impl<W: Word, M: BlockMode<W>> StreamMode for ModeStream<W, M> {
+    fn update(&mut self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(ModeStream::update(self, input))
+    }
+
+    fn finalize(self) -> Result<Vec<u8>, Error> {
+        ModeStream::finalize(self)
+    }
+}
+
+/// Pass `input` through `buffer` and all complete blocks out of it,
+/// except the last `hold` bytes, through `mode`
+pub(crate) fn process_buffered<W: Word, M: BlockMode<W>>(
+    mode: &mut M,
+    buffer: &mut BlockBuffer<W>,
+    input: &[u8],
+    hold: usize,
+) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len() + W::BLOCK_BYTES);
+    for block in buffer.push(input, hold) {
+        append_block(&mut output, mode.process_block(block));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cipher::{DecodeBlock, EncodeBlock},
+        mode::conformance,
+        schedule::KeySchedule,
+        settings::Rc5Settings,
+    };
+
+    /// User-defined mode without IV: each block is XORed
+    /// with the previous plaintext block after encoding
+    struct Chained<C, W: Word> {
+        cipher: C,
+        previous: (W, W),
+    }
+
+    struct Unchained<C, W: Word> {
+        cipher: C,
+        previous: (W, W),
+    }
+
+    impl<W: Word, C: EncodeBlock<W>> BlockMode<W> for Chained<C, W> {
+        type Cipher = C;
+        type Iv = [(W, W); 0];
+        const DIRECTION: Direction = Direction::Encode;
+
+        fn init(cipher: C, _iv: [(W, W); 0]) -> Self {
+            Self {
+                cipher,
+                previous: (W::zero(), W::zero()),
+            }
+        }
+
+        fn process_block(&mut self, block: (W, W)) -> (W, W) {
+            let (a, b) = self.cipher.encode_block(block);
+            let output = (a ^ self.previous.0, b ^ self.previous.1);
+            self.previous = block;
+            output
+        }
+    }
+
+    impl<W: Word, C: DecodeBlock<W>> BlockMode<W> for Unchained<C, W> {
+        type Cipher = C;
+        type Iv = [(W, W); 0];
+        const DIRECTION: Direction = Direction::Decode;
+
+        fn init(cipher: C, _iv: [(W, W); 0]) -> Self {
+            Self {
+                cipher,
+                previous: (W::zero(), W::zero()),
+            }
+        }
+
+        fn process_block(&mut self, (a, b): (W, W)) -> (W, W) {
+            self.previous = self
+                .cipher
+                .decode_block((a ^ self.previous.0, b ^ self.previous.1));
+            self.previous
+        }
+    }
+
+    #[test]
+    fn test_user_defined_mode() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u16>::new(12));
+        conformance::check_block_mode::<_, Chained<_, _>, Unchained<_, _>>(
+            || &schedule,
+            || &schedule,
+        );
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u64>::new(12));
+        conformance::check_block_mode::<_, Chained<_, _>, Unchained<_, _>>(
+            || &schedule,
+            || &schedule,
+        );
+    }
+
+    #[test]
+    fn test_typed_iv() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u16>::new(12));
+        let pt = (0..16).collect::<Vec<u8>>();
+        let typed = ModeStream::<_, crate::IgeEncoder<_, _>>::new(&schedule, [(1, 2), (3, 4)]);
+        let loaded = ModeStream::<_, crate::IgeEncoder<_, _>>::init(
+            &schedule,
+            &[0x01, 0x00, 0x02, 0x00, 0x03, 0x00, 0x04, 0x00],
+        )
+        .unwrap();
+        assert_eq!(typed.process_all(&pt), loaded.process_all(&pt));
+
+        assert_eq!(
+            <[(u16, u16); 2]>::from_bytes(&[0x00; 7]),
+            Err(Error::WrongIvSize)
+        );
+        assert_eq!(<[(u16, u16); 0]>::from_bytes(&[]), Ok([]));
+    }
+
+    #[test]
+    fn test_wrong_padding() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::default());
+        let ct = ModeStream::<_, Chained<_, _>>::init(&schedule, &[])
+            .unwrap()
+            .process_all(&[0x00; 16])
+            .unwrap();
+        assert_eq!(
+            ModeStream::<_, Unchained<_, _>>::init_padded(&schedule, &[], crate::Pkcs7)
+                .unwrap()
+                .process_all(&ct),
+            Err(Error::WrongPadding)
+        );
+    }
+}
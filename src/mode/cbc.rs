@@ -0,0 +1,248 @@
+use crate::{
+    block::xor_block,
+    cipher::{DecodeBlock, EncodeBlock},
+    mode::{BlockMode, Direction, ModeStream},
+    word::Word,
+    Error,
+};
+
+/// RC5 in Cipher Block Chaining mode, encryption side
+///
+/// Each plaintext block is XORed with the previous ciphertext block
+/// (the initialization vector for the first one) before encoding,
+/// so equal plaintext blocks don't give equal ciphertext blocks.
+/// This is RC5-CBC from [RFC 2040](https://www.rfc-editor.org/rfc/rfc2040#section-7).
+///
+/// The input must be a multiple of the block size. [`CbcEncoder::new`] gives
+/// a [`ModeStream`]: use [`ModeStream::update`] for streaming
+/// or [`ModeStream::process_all`] for one-shot
+pub struct CbcEncoder<W: Word, C: EncodeBlock<W>> {
+    cipher: C,
+    chain: (W, W),
+}
+
+impl<W: Word, C: EncodeBlock<W>> CbcEncoder<W, C> {
+    /// `Error` - if `iv` is not exactly one block long
+    pub fn new(cipher: C, iv: &[u8]) -> Result<ModeStream<W, Self>, Error> {
+        ModeStream::init(cipher, iv)
+    }
+}
+
+impl<W: Word, C: EncodeBlock<W>> BlockMode<W> for CbcEncoder<W, C> {
+    type Cipher = C;
+    type Iv = [(W, W); 1];
+    const DIRECTION: Direction = Direction::Encode;
+
+    fn init(cipher: C, [chain]: [(W, W); 1]) -> Self {
+        Self { cipher, chain }
+    }
+
+    fn process_block(&mut self, block: (W, W)) -> (W, W) {
+        self.chain = self.cipher.encode_block(xor_block(block, self.chain));
+        self.chain
+    }
+}
+
+/// RC5 in Cipher Block Chaining mode, decryption side
+///
+/// Check [`CbcEncoder`] for details
+pub struct CbcDecoder<W: Word, C: DecodeBlock<W>> {
+    cipher: C,
+    chain: (W, W),
+}
+
+impl<W: Word, C: DecodeBlock<W>> CbcDecoder<W, C> {
+    /// `Error` - if `iv` is not exactly one block long
+    pub fn new(cipher: C, iv: &[u8]) -> Result<ModeStream<W, Self>, Error> {
+        ModeStream::init(cipher, iv)
+    }
+
+    pub(crate) fn cipher(&self) -> &C {
+        &self.cipher
+    }
+}
+
+impl<W: Word, C: DecodeBlock<W>> BlockMode<W> for CbcDecoder<W, C> {
+    type Cipher = C;
+    type Iv = [(W, W); 1];
+    const DIRECTION: Direction = Direction::Decode;
+
+    fn init(cipher: C, [chain]: [(W, W); 1]) -> Self {
+        Self { cipher, chain }
+    }
+
+    fn process_block(&mut self, block: (W, W)) -> (W, W) {
+        let decoded = xor_block(self.cipher.decode_block(block), self.chain);
+        self.chain = block;
+        decoded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{key::Key, schedule::KeySchedule, settings::Rc5Settings};
+
+    fn check_rfc2040(rounds_count: u8, key: impl Key, iv: &str, pt: &str, ct: &str) {
+        let schedule = KeySchedule::new(key, Rc5Settings::<u32>::new(rounds_count));
+        let (iv, pt, ct) = (
+            hex::decode(iv).unwrap(),
+            hex::decode(pt).unwrap(),
+            hex::decode(ct).unwrap(),
+        );
+        assert_eq!(
+            CbcEncoder::new(&schedule, &iv).unwrap().process_all(&pt),
+            Ok(ct.clone()),
+            "encode R = {rounds_count}"
+        );
+        assert_eq!(
+            CbcDecoder::new(&schedule, &iv).unwrap().process_all(&ct),
+            Ok(pt),
+            "decode R = {rounds_count}"
+        );
+    }
+
+    /// Test vectors for RC5-CBC from section 9 of
+    /// [RFC 2040](https://www.rfc-editor.org/rfc/rfc2040#section-9)
+    #[test]
+    fn test_rfc2040() {
+        let iv0 = "0000000000000000";
+        let iv = "0102030405060708";
+        let pt = "1020304050607080";
+        let ones = "ffffffffffffffff";
+        let key8 = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+        let key16 = [
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x10, 0x20, 0x30, 0x40, 0x50, 0x60,
+            0x70, 0x80,
+        ];
+
+        check_rfc2040(0, [0x00], iv0, iv0, "7a7bba4d79111d1e");
+        check_rfc2040(0, [0x00], iv0, ones, "797bba4d78111d1e");
+        check_rfc2040(0, [0x00], "0000000000000001", iv0, "7a7bba4d79111d1f");
+        check_rfc2040(0, [0x00], iv0, "0000000000000001", "7a7bba4d79111d1f");
+        check_rfc2040(0, [0x00], iv, pt, "8b9ded91ce7794a6");
+        check_rfc2040(1, [0x11], iv0, iv0, "2f759fe7ad86a378");
+        check_rfc2040(2, [0x00], iv0, iv0, "dca2694bf40e0788");
+        check_rfc2040(8, [0x00], iv0, iv0, "dcfe098577eca5ff");
+        check_rfc2040(8, [0x00], iv, pt, "9646fb77638f9ca8");
+        check_rfc2040(12, [0x00], iv, pt, "b2b3209db6594da4");
+        check_rfc2040(16, [0x00], iv, pt, "545f7f32a5fc3836");
+        check_rfc2040(8, [0x01, 0x02, 0x03, 0x04], iv0, ones, "8285e7c1b5bc7402");
+        check_rfc2040(12, [0x01, 0x02, 0x03, 0x04], iv0, ones, "fc586f92f7080934");
+        check_rfc2040(16, [0x01, 0x02, 0x03, 0x04], iv0, ones, "cf270ef9717ff7c4");
+        check_rfc2040(12, key8, iv0, ones, "e493f1c1bb4d6e8c");
+        check_rfc2040(8, key8, iv, pt, "5c4c041e0f217ac3");
+        check_rfc2040(12, key8, iv, pt, "921f12485373b4f7");
+        check_rfc2040(16, key8, iv, pt, "5ba0ca6bbe7f5fad");
+        check_rfc2040(8, key16, iv, pt, "c533771cd0110e63");
+        check_rfc2040(12, key16, iv, pt, "294ddb46b3278d60");
+        check_rfc2040(16, key16, iv, pt, "dad6bda9dfe8f7e8");
+        check_rfc2040(
+            12,
+            [0x01, 0x02, 0x03, 0x04, 0x05],
+            iv0,
+            ones,
+            "97e0787837ed317f",
+        );
+    }
+
+    #[test]
+    fn test_chaining() {
+        // The first RFC 2040 block chains into the second one as its IV
+        let schedule = KeySchedule::new([0x01, 0x02, 0x03, 0x04, 0x05], Rc5Settings::<u32>::new(8));
+        let pt = hex::decode("ffffffffffffffff0808080808080808").unwrap();
+        let ct = hex::decode("7875dbf6738c64788f34c3c681c99695").unwrap();
+
+        assert_eq!(
+            CbcEncoder::new(&schedule, &[0x00; 8])
+                .unwrap()
+                .process_all(&pt),
+            Ok(ct.clone())
+        );
+        assert_eq!(
+            CbcDecoder::new(&schedule, &[0x00; 8])
+                .unwrap()
+                .process_all(&ct),
+            Ok(pt)
+        );
+    }
+
+    #[test]
+    fn test_incremental() {
+        let schedule = KeySchedule::new([0x00; 16], Rc5Settings::<u64>::new(20));
+        let iv = [0x42; 16];
+        let pt = (0..160).collect::<Vec<u8>>();
+        let ct = CbcEncoder::new(&schedule, &iv)
+            .unwrap()
+            .process_all(&pt)
+            .unwrap();
+        assert_eq!(ct.len(), pt.len());
+        // Identical plaintext blocks must not give identical ciphertext blocks
+        assert_ne!(
+            CbcEncoder::new(&schedule, &iv)
+                .unwrap()
+                .process_all(&[0x00; 32])
+                .unwrap()[..16],
+            CbcEncoder::new(&schedule, &iv)
+                .unwrap()
+                .process_all(&[0x00; 32])
+                .unwrap()[16..]
+        );
+
+        for step in [1, 7, 16, 33] {
+            let mut encoder = CbcEncoder::new(&schedule, &iv).unwrap();
+            let mut decoder = CbcDecoder::new(&schedule, &iv).unwrap();
+            let (mut encoded, mut decoded) = (vec![], vec![]);
+            for chunk in pt.chunks(step) {
+                encoded.extend(encoder.update(chunk));
+            }
+            encoded.extend(encoder.finalize().unwrap());
+            for chunk in ct.chunks(step) {
+                decoded.extend(decoder.update(chunk));
+            }
+            decoded.extend(decoder.finalize().unwrap());
+
+            assert_eq!(encoded, ct, "step {step}");
+            assert_eq!(decoded, pt, "step {step}");
+        }
+    }
+
+    #[test]
+    fn test_conformance() {
+        use crate::mode::conformance;
+
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u32>::new(12));
+        conformance::check_block_mode::<_, CbcEncoder<_, _>, CbcDecoder<_, _>>(
+            || &schedule,
+            || &schedule,
+        );
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u128>::new(12));
+        conformance::check_block_mode::<_, CbcEncoder<_, _>, CbcDecoder<_, _>>(
+            || &schedule,
+            || &schedule,
+        );
+    }
+
+    #[test]
+    fn test_wrong_input() {
+        let schedule = KeySchedule::new([0x00; 16], Rc5Settings::default());
+        assert!(matches!(
+            CbcEncoder::new(&schedule, &[0x00; 7]),
+            Err(Error::WrongIvSize)
+        ));
+        assert!(matches!(
+            CbcDecoder::new(&schedule, &[0x00; 16]),
+            Err(Error::WrongIvSize)
+        ));
+        assert_eq!(
+            CbcEncoder::new(&schedule, &[0x00; 8])
+                .unwrap()
+                .process_all(&[0x00; 9]),
+            Err(Error::WrongInputSize)
+        );
+
+        let mut decoder = CbcDecoder::new(&schedule, &[0x00; 8]).unwrap();
+        assert_eq!(decoder.update(&[0x00; 12]).len(), 8);
+        assert_eq!(decoder.finalize(), Err(Error::WrongInputSize));
+    }
+}
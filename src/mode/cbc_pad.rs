@@ -0,0 +1,219 @@
+use crate::{
+    cipher::{DecodeBlock, EncodeBlock},
+    mode::{CbcDecoder, CbcEncoder, ModeStream, StreamMode},
+    padding::Pkcs7,
+    word::Word,
+    Error,
+};
+
+/// RC5-CBC-Pad from [RFC 2040](https://www.rfc-editor.org/rfc/rfc2040#section-8), encryption side
+///
+/// Same as [`CbcEncoder`], but the input can be of any length:
+/// it's padded with `n` bytes of value `n` up to the next multiple
+/// of the block size (PKCS#5 style), so the output is always
+/// from 1 to `W::BLOCK_BYTES` bytes longer than the input
+pub struct CbcPadEncoder<W: Word, C: EncodeBlock<W>> {
+    stream: ModeStream<W, CbcEncoder<W, C>>,
+}
+
+impl<W: Word, C: EncodeBlock<W>> CbcPadEncoder<W, C> {
+    /// `Error` - if `iv` is not exactly one block long
+    pub fn new(cipher: C, iv: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            stream: ModeStream::init_padded(cipher, iv, Pkcs7)?,
+        })
+    }
+
+    /// Encode all complete blocks available so far,
+    /// the rest is kept until the next call
+    pub fn update(&mut self, input: &[u8]) -> Vec<u8> {
+        self.stream.update(input)
+    }
+
+    /// Pad and encode the last block
+    pub fn finalize(self) -> Vec<u8> {
+        self.stream
+            .finalize()
+            .expect("The padded input is always a multiple of the block size")
+    }
+
+    /// One-shot encoding of the whole `input`
+    pub fn encode(mut self, input: &[u8]) -> Vec<u8> {
+        let mut output = self.update(input);
+        output.extend(self.finalize());
+        output
+    }
+}
+
+impl<W: Word, C: EncodeBlock<W>> StreamMode for CbcPadEncoder<W, C> {
+    fn update(&mut self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(CbcPadEncoder::update(self, input))
+    }
+
+    fn finalize(self) -> Result<Vec<u8>, Error> {
+        Ok(CbcPadEncoder::finalize(self))
+    }
+}
+
+/// RC5-CBC-Pad from [RFC 2040](https://www.rfc-editor.org/rfc/rfc2040#section-8), decryption side
+///
+/// Check [`CbcPadEncoder`] for details. The last block is kept
+/// until [`CbcPadDecoder::finalize`], because it contains the padding
+pub struct CbcPadDecoder<W: Word, C: DecodeBlock<W>> {
+    stream: ModeStream<W, CbcDecoder<W, C>>,
+}
+
+impl<W: Word, C: DecodeBlock<W>> CbcPadDecoder<W, C> {
+    /// `Error` - if `iv` is not exactly one block long
+    pub fn new(cipher: C, iv: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            stream: ModeStream::init_padded(cipher, iv, Pkcs7)?,
+        })
+    }
+
+    /// Decode all complete blocks available so far, except the last one
+    pub fn update(&mut self, input: &[u8]) -> Vec<u8> {
+        self.stream.update(input)
+    }
+
+    /// Decode the last block and remove the padding
+    ///
+    /// `Error::WrongInputSize` - if the total input is empty or cannot be divided into blocks
+    /// `Error::WrongPadding` - if the decoded padding is malformed
+    pub fn finalize(self) -> Result<Vec<u8>, Error> {
+        self.stream.finalize()
+    }
+
+    /// One-shot decoding of the whole `input`
+    ///
+    /// Check [`CbcPadDecoder::finalize`] for errors
+    pub fn decode(mut self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut output = self.update(input);
+        output.extend(self.finalize()?);
+        Ok(output)
+    }
+}
+
+impl<W: Word, C: DecodeBlock<W>> StreamMode for CbcPadDecoder<W, C> {
+    fn update(&mut self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(CbcPadDecoder::update(self, input))
+    }
+
+    fn finalize(self) -> Result<Vec<u8>, Error> {
+        CbcPadDecoder::finalize(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schedule::KeySchedule, settings::Rc5Settings};
+
+    /// The last two RC5-CBC vectors of [RFC 2040](https://www.rfc-editor.org/rfc/rfc2040#section-9)
+    /// (R = 8, key = 0102030405) chain into RC5-CBC-Pad of a single block:
+    /// the second one encodes the padding block with the first ciphertext as IV
+    #[test]
+    fn test_rfc2040() {
+        let schedule = KeySchedule::new([0x01, 0x02, 0x03, 0x04, 0x05], Rc5Settings::<u32>::new(8));
+        let pt = hex::decode("ffffffffffffffff").unwrap();
+        let ct = hex::decode("7875dbf6738c64788f34c3c681c99695").unwrap();
+
+        assert_eq!(
+            CbcPadEncoder::new(&schedule, &[0x00; 8])
+                .unwrap()
+                .encode(&pt),
+            ct
+        );
+        assert_eq!(
+            CbcPadDecoder::new(&schedule, &[0x00; 8])
+                .unwrap()
+                .decode(&ct),
+            Ok(pt)
+        );
+    }
+
+    #[test]
+    fn test_vectors() {
+        let key: [u8; 16] = (0..16).collect::<Vec<_>>().try_into().unwrap();
+        let schedule = KeySchedule::new(key, Rc5Settings::default());
+        let iv = hex::decode("0102030405060708").unwrap();
+        let pt = hex::decode("5243352d4342432d506164").unwrap();
+        let ct = hex::decode("c08e35dee7999b740eca35cb7328df18").unwrap();
+        assert_eq!(CbcPadEncoder::new(&schedule, &iv).unwrap().encode(&pt), ct);
+        assert_eq!(
+            CbcPadDecoder::new(&schedule, &iv).unwrap().decode(&ct),
+            Ok(pt)
+        );
+
+        let key: [u8; 24] = (0..24).collect::<Vec<_>>().try_into().unwrap();
+        let schedule = KeySchedule::new(key, Rc5Settings::<u64>::new(16));
+        let iv = (16..32).collect::<Vec<u8>>();
+        let pt = (0..20).collect::<Vec<u8>>();
+        let ct = hex::decode("9d2d2aca6e1f0e194fb262d07d073544029b5ba0b2092c004e9f160955f84b6c")
+            .unwrap();
+        assert_eq!(CbcPadEncoder::new(&schedule, &iv).unwrap().encode(&pt), ct);
+        assert_eq!(
+            CbcPadDecoder::new(&schedule, &iv).unwrap().decode(&ct),
+            Ok(pt)
+        );
+    }
+
+    #[test]
+    fn test_any_length() {
+        let schedule = KeySchedule::new([0x0f; 8], Rc5Settings::<u16>::new(16));
+        let iv = [0xa5; 4];
+        for len in 0..20 {
+            let pt = (0..len).collect::<Vec<u8>>();
+            let ct = CbcPadEncoder::new(&schedule, &iv).unwrap().encode(&pt);
+            assert_eq!(ct.len(), (len as usize / 4 + 1) * 4);
+
+            for step in [1, 3, 4, 64] {
+                let mut decoder = CbcPadDecoder::new(&schedule, &iv).unwrap();
+                let mut decoded = vec![];
+                for chunk in ct.chunks(step) {
+                    decoded.extend(decoder.update(chunk));
+                }
+                decoded.extend(decoder.finalize().unwrap());
+                assert_eq!(decoded, pt, "len {len}, step {step}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_conformance() {
+        use crate::mode::conformance;
+
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u16>::new(12));
+        let iv = [0x24; 4];
+        conformance::check_stream_mode::<u16, _, _>(
+            || CbcPadEncoder::new(&schedule, &iv).unwrap(),
+            || CbcPadDecoder::new(&schedule, &iv).unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_wrong_input() {
+        let schedule = KeySchedule::new([0x01; 16], Rc5Settings::default());
+        let iv = [0x00; 8];
+        assert_eq!(
+            CbcPadDecoder::new(&schedule, &iv).unwrap().decode(&[]),
+            Err(Error::WrongInputSize)
+        );
+        assert_eq!(
+            CbcPadDecoder::new(&schedule, &iv)
+                .unwrap()
+                .decode(&[0x00; 12]),
+            Err(Error::WrongInputSize)
+        );
+
+        // Without a valid padding block at the end
+        let ct = CbcEncoder::new(&schedule, &iv)
+            .unwrap()
+            .process_all(&[0x00; 16])
+            .unwrap();
+        assert_eq!(
+            CbcPadDecoder::new(&schedule, &iv).unwrap().decode(&ct),
+            Err(Error::WrongPadding)
+        );
+    }
+}
@@ -0,0 +1,275 @@
+use std::marker::PhantomData;
+
+use crate::{
+    block::{read_block, write_block},
+    cipher::EncodeBlock,
+    mode::{read_iv, StreamMode},
+    word::Word,
+    Error,
+};
+
+/// Shift register and keystream of the current segment,
+/// shared by both sides of the mode
+struct CfbState<W: Word, C: EncodeBlock<W>> {
+    cipher: C,
+    segment_bytes: usize,
+    register: Vec<u8>,
+    keystream: Vec<u8>,
+    /// Ciphertext bytes of the current segment
+    feedback: Vec<u8>,
+    _p: PhantomData<W>,
+}
+
+impl<W: Word, C: EncodeBlock<W>> CfbState<W, C> {
+    fn new(cipher: C, iv: &[u8], segment_bits: usize) -> Result<Self, Error> {
+        read_iv::<W>(iv)?;
+        if segment_bits == 0 || segment_bits % 8 != 0 || segment_bits > W::BLOCK_BYTES * 8 {
+            return Err(Error::WrongSegmentSize);
+        }
+        let segment_bytes = segment_bits / 8;
+
+        Ok(Self {
+            cipher,
+            segment_bytes,
+            register: iv.to_vec(),
+            keystream: vec![0x00; W::BLOCK_BYTES],
+            feedback: Vec::with_capacity(segment_bytes),
+            _p: PhantomData::default(),
+        })
+    }
+
+    /// Process one byte, `ciphertext` selects which of the
+    /// input & output bytes is shifted into the register
+    fn process_byte(&mut self, byte: u8, ciphertext: impl Fn(u8, u8) -> u8) -> u8 {
+        if self.feedback.is_empty() {
+            write_block(
+                self.cipher.encode_block(read_block(&self.register)),
+                &mut self.keystream,
+            );
+        }
+        let output = byte ^ self.keystream[self.feedback.len()];
+        self.feedback.push(ciphertext(byte, output));
+
+        if self.feedback.len() == self.segment_bytes {
+            self.register.drain(..self.segment_bytes);
+            self.register.append(&mut self.feedback);
+        }
+        output
+    }
+}
+
+/// RC5 in Cipher Feedback mode, encryption side
+///
+/// The encoded shift register (the initialization vector at the start) gives
+/// the keystream for the next segment of `segment_bits`, from 8 bits (CFB-8)
+/// up to the full block. The ciphertext of the segment is shifted into the register.
+///
+/// It's a stream mode: the input can be of any length and is processed byte by byte
+pub struct CfbEncoder<W: Word, C: EncodeBlock<W>> {
+    state: CfbState<W, C>,
+}
+
+impl<W: Word, C: EncodeBlock<W>> CfbEncoder<W, C> {
+    /// `Error::WrongIvSize` - if `iv` is not exactly one block long
+    /// `Error::WrongSegmentSize` - if `segment_bits` is not a whole count of bytes
+    /// from one byte to the block size
+    pub fn new(cipher: C, iv: &[u8], segment_bits: usize) -> Result<Self, Error> {
+        Ok(Self {
+            state: CfbState::new(cipher, iv, segment_bits)?,
+        })
+    }
+
+    /// Encode `input` right away, it can be as short as one byte
+    pub fn update(&mut self, input: &[u8]) -> Vec<u8> {
+        input
+            .iter()
+            .map(|byte| self.state.process_byte(*byte, |_, output| output))
+            .collect()
+    }
+
+    /// One-shot encoding of the whole `input`
+    pub fn encode(mut self, input: &[u8]) -> Vec<u8> {
+        self.update(input)
+    }
+}
+
+impl<W: Word, C: EncodeBlock<W>> StreamMode for CfbEncoder<W, C> {
+    fn update(&mut self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(CfbEncoder::update(self, input))
+    }
+
+    fn finalize(self) -> Result<Vec<u8>, Error> {
+        Ok(vec![])
+    }
+}
+
+/// RC5 in Cipher Feedback mode, decryption side
+///
+/// Check [`CfbEncoder`] for details
+pub struct CfbDecoder<W: Word, C: EncodeBlock<W>> {
+    state: CfbState<W, C>,
+}
+
+impl<W: Word, C: EncodeBlock<W>> CfbDecoder<W, C> {
+    /// Check [`CfbEncoder::new`] for errors
+    pub fn new(cipher: C, iv: &[u8], segment_bits: usize) -> Result<Self, Error> {
+        Ok(Self {
+            state: CfbState::new(cipher, iv, segment_bits)?,
+        })
+    }
+
+    /// Decode `input` right away, it can be as short as one byte
+    pub fn update(&mut self, input: &[u8]) -> Vec<u8> {
+        input
+            .iter()
+            .map(|byte| self.state.process_byte(*byte, |input, _| input))
+            .collect()
+    }
+
+    /// One-shot decoding of the whole `input`
+    pub fn decode(mut self, input: &[u8]) -> Vec<u8> {
+        self.update(input)
+    }
+}
+
+impl<W: Word, C: EncodeBlock<W>> StreamMode for CfbDecoder<W, C> {
+    fn update(&mut self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(CfbDecoder::update(self, input))
+    }
+
+    fn finalize(self) -> Result<Vec<u8>, Error> {
+        Ok(vec![])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schedule::KeySchedule, settings::Rc5Settings};
+
+    fn check<W: Word>(schedule: &KeySchedule<W>, iv: &[u8], segment_bits: usize, ct: &str) {
+        let ct = hex::decode(ct).unwrap();
+        let pt = (0..ct.len() as u8).collect::<Vec<u8>>();
+        assert_eq!(
+            CfbEncoder::new(schedule, iv, segment_bits)
+                .unwrap()
+                .encode(&pt),
+            ct,
+            "encode CFB-{segment_bits}"
+        );
+        assert_eq!(
+            CfbDecoder::new(schedule, iv, segment_bits)
+                .unwrap()
+                .decode(&ct),
+            pt,
+            "decode CFB-{segment_bits}"
+        );
+    }
+
+    #[test]
+    fn test_vectors() {
+        let key: [u8; 16] = (0..16).collect::<Vec<_>>().try_into().unwrap();
+
+        let schedule = KeySchedule::new(key, Rc5Settings::default());
+        let iv = hex::decode("0102030405060708").unwrap();
+        check(
+            &schedule,
+            &iv,
+            8,
+            "73b19b20064c7a3f518bdcbe3c42931a5586a93f",
+        );
+        check(
+            &schedule,
+            &iv,
+            24,
+            "73469777e359c04023f0eee7ada179f310cb4190",
+        );
+        check(
+            &schedule,
+            &iv,
+            64,
+            "734697fb2ebaf7bd5b5b60d1e18f5f1f7b3913f0",
+        );
+
+        let schedule = KeySchedule::new(key, Rc5Settings::<u64>::new(16));
+        let iv = (16..32).collect::<Vec<u8>>();
+        check(
+            &schedule,
+            &iv,
+            8,
+            "c7424cf12e03a7dd80fa8f09b24550d1b22cfd96692126a003309b2a6784f5df855042eef2027e6a",
+        );
+        check(
+            &schedule,
+            &iv,
+            128,
+            "c70274968396fe15ecb6066654955e824d1749aecbe36b0111b8f2b7233d2bb7830fe217f3caa72d",
+        );
+
+        let key = [0x00, 0x01, 0x02, 0x03];
+        let schedule = KeySchedule::new(key, Rc5Settings::<u16>::new(12));
+        check(
+            &schedule,
+            &[0xa0, 0xa1, 0xa2, 0xa3],
+            8,
+            "d351e649c0d338cb5843",
+        );
+        let schedule = KeySchedule::new(key, Rc5Settings::<u8>::new(12));
+        check(&schedule, &[0xa0, 0xa1], 8, "5b8242550b23ebb678c9");
+    }
+
+    #[test]
+    fn test_byte_at_a_time() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u32>::new(12));
+        let iv = [0x24; 8];
+        let pt = (0..=100).collect::<Vec<u8>>();
+
+        for segment_bits in [8, 16, 40, 64] {
+            let ct = CfbEncoder::new(&schedule, &iv, segment_bits)
+                .unwrap()
+                .encode(&pt);
+            for step in [1, 3, 8, 64] {
+                let mut encoder = CfbEncoder::new(&schedule, &iv, segment_bits).unwrap();
+                let mut decoder = CfbDecoder::new(&schedule, &iv, segment_bits).unwrap();
+                let (mut encoded, mut decoded) = (vec![], vec![]);
+                for chunk in pt.chunks(step) {
+                    encoded.extend(encoder.update(chunk));
+                }
+                for chunk in ct.chunks(step) {
+                    decoded.extend(decoder.update(chunk));
+                }
+                assert_eq!(encoded, ct, "CFB-{segment_bits}, step {step}");
+                assert_eq!(decoded, pt, "CFB-{segment_bits}, step {step}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_conformance() {
+        use crate::mode::conformance;
+
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u64>::new(16));
+        let iv = [0x24; 16];
+        for segment_bits in [8, 24, 128] {
+            conformance::check_stream_mode::<u64, _, _>(
+                || CfbEncoder::new(&schedule, &iv, segment_bits).unwrap(),
+                || CfbDecoder::new(&schedule, &iv, segment_bits).unwrap(),
+            );
+        }
+    }
+
+    #[test]
+    fn test_wrong_settings() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::default());
+        for segment_bits in [0, 4, 12, 72] {
+            assert!(matches!(
+                CfbEncoder::new(&schedule, &[0x00; 8], segment_bits),
+                Err(Error::WrongSegmentSize)
+            ));
+        }
+        assert!(matches!(
+            CfbDecoder::new(&schedule, &[0x00; 4], 8),
+            Err(Error::WrongIvSize)
+        ));
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{
+    mode::{BlockMode, Direction, ModeIv, ModeStream, StreamMode},
+    padding::{AnsiX923, Iso7816, Pkcs7, ZeroPadding},
+    word::Word,
+    Error,
+};
+
+/// Feed `input` to `mode` by chunks of `step` bytes
+fn chunked(mut mode: impl StreamMode, input: &[u8], step: usize) -> Result<Vec<u8>, Error> {
+    let mut output = vec![];
+    for chunk in input.chunks(step) {
+        output.extend(mode.update(chunk)?);
+    }
+    output.extend(mode.finalize()?);
+    Ok(output)
+}
+
+/// Checks for any [`StreamMode`]: messages of every length up to four blocks
+/// of `W` give the same result by chunks of any size as in one go,
+/// and every message the encoder accepts is decoded back.
+/// `encoder` and `decoder` must start the mode with the same key and IV every time
+///
+/// Panics on the first failed check
+pub fn check_stream_mode<W, E, D>(encoder: impl Fn() -> E, decoder: impl Fn() -> D)
+where
+    W: Word,
+    E: StreamMode,
+    D: StreamMode,
+{
+    let block_len = W::BLOCK_BYTES;
+    let steps = [1, 3, block_len, 2 * block_len + 1];
+    let mut accepted = 0;
+
+    for len in 0..=4 * block_len + 1 {
+        // No zero bytes, so zero padding can be removed exactly
+        let pt = (0..len)
+            .map(|index| (index % 255 + 1) as u8)
+            .collect::<Vec<_>>();
+        let ct = encoder().process_all(&pt);
+        for step in steps {
+            assert_eq!(
+                chunked(encoder(), &pt, step),
+                ct,
+                "encoding {len} bytes by {step}"
+            );
+        }
+
+        let ct = match ct {
+            Ok(ct) => ct,
+            Err(_) => continue,
+        };
+        accepted += 1;
+        if len >= 2 * block_len {
+            assert!(!ct.starts_with(&pt), "{len} bytes are not encoded");
+        }
+        for step in steps {
+            assert_eq!(
+                chunked(decoder(), &ct, step),
+                Ok(pt.clone()),
+                "decoding {len} bytes by {step}"
+            );
+        }
+    }
+    assert_ne!(accepted, 0, "no message is accepted");
+}
+
+fn padded<W: Word, M: BlockMode<W>>(
+    cipher: M::Cipher,
+    iv: &[u8],
+    scheme: usize,
+) -> ModeStream<W, M> {
+    match scheme {
+        0 => ModeStream::init_padded(cipher, iv, Pkcs7),
+        1 => ModeStream::init_padded(cipher, iv, AnsiX923),
+        2 => ModeStream::init_padded(cipher, iv, Iso7816),
+        _ => ModeStream::init_padded(cipher, iv, ZeroPadding),
+    }
+    .unwrap()
+}
+
+/// Checks for the two sides of a [`BlockMode`] driven by [`ModeStream`]:
+/// the IV size, block-aligned input without padding, every padding scheme
+/// and everything of [`check_stream_mode`] with and without padding.
+/// `encryptor` and `decryptor` must give the same key every time
+///
+/// Panics on the first failed check
+pub fn check_block_mode<W, E, D>(
+    encryptor: impl Fn() -> E::Cipher,
+    decryptor: impl Fn() -> D::Cipher,
+) where
+    W: Word,
+    E: BlockMode<W>,
+    D: BlockMode<W>,
+{
+    assert_eq!(E::DIRECTION, Direction::Encode);
+    assert_eq!(D::DIRECTION, Direction::Decode);
+    assert_eq!(E::Iv::BLOCKS, D::Iv::BLOCKS);
+
+    let block_len = W::BLOCK_BYTES;
+    let iv = (0..E::Iv::BLOCKS * block_len)
+        .map(|index| (index * 7 + 1) as u8)
+        .collect::<Vec<_>>();
+    let encoder = || ModeStream::<W, E>::init(encryptor(), &iv).unwrap();
+    let decoder = || ModeStream::<W, D>::init(decryptor(), &iv).unwrap();
+
+    for wrong_len in [iv.len() + 1, iv.len() + block_len] {
+        assert!(matches!(
+            ModeStream::<W, E>::init(encryptor(), &vec![0x00; wrong_len]),
+            Err(Error::WrongIvSize)
+        ));
+        assert!(matches!(
+            ModeStream::<W, D>::init(decryptor(), &vec![0x00; wrong_len]),
+            Err(Error::WrongIvSize)
+        ));
+    }
+
+    // Without padding only whole blocks are accepted and the length is kept
+    check_stream_mode::<W, _, _>(encoder, decoder);
+    for len in 0..3 * block_len {
+        let pt = vec![0x01; len];
+        match len % block_len {
+            0 => assert_eq!(encoder().process_all(&pt).map(|ct| ct.len()), Ok(len)),
+            _ => {
+                assert_eq!(encoder().process_all(&pt), Err(Error::WrongInputSize));
+                assert_eq!(decoder().process_all(&pt), Err(Error::WrongInputSize));
+            }
+        }
+    }
+
+    let unaligned = vec![0x00; block_len + 1];
+    for scheme in 0..4 {
+        check_stream_mode::<W, _, _>(
+            || padded::<W, E>(encryptor(), &iv, scheme),
+            || padded::<W, D>(decryptor(), &iv, scheme),
+        );
+        assert_eq!(
+            padded::<W, D>(decryptor(), &iv, scheme).process_all(&unaligned),
+            Err(Error::WrongInputSize)
+        );
+    }
+
+    // The padding is always added, except the zero one
+    for scheme in 0..3 {
+        for len in 0..2 * block_len {
+            let ct = padded::<W, E>(encryptor(), &iv, scheme)
+                .process_all(&vec![0x01; len])
+                .unwrap();
+            assert_eq!(ct.len(), (len / block_len + 1) * block_len);
+        }
+    }
+    let pt = vec![0x01; 2 * block_len];
+    let ct = padded::<W, E>(encryptor(), &iv, 3)
+        .process_all(&pt)
+        .unwrap();
+    assert_eq!(ct.len(), pt.len());
+    assert_eq!(
+        padded::<W, D>(decryptor(), &iv, 3).process_all(&[]),
+        Ok(vec![])
+    );
+}
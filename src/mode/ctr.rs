@@ -0,0 +1,229 @@
+use std::marker::PhantomData;
+
+use crate::{
+    block::{read_block, write_block},
+    cipher::EncodeBlock,
+    mode::StreamMode,
+    word::Word,
+    Error,
+};
+
+/// RC5 in Counter mode
+///
+/// The keystream block `i` is the encoding of `nonce | i`, where the counter `i`
+/// is big-endian and takes all bytes of the block that are not taken by the nonce.
+/// So the length of the nonce sets the split: the shorter the nonce, the longer the
+/// message can be. Encoding and decoding are the same operation.
+///
+/// The keystream is seekable: [`Ctr::seek`] moves to any byte
+/// offset without processing the data before it
+pub struct Ctr<W: Word, C: EncodeBlock<W>> {
+    cipher: C,
+    nonce: Vec<u8>,
+    /// Count of blocks the counter can address,
+    /// `None` if it's more than `u64` anyway
+    blocks_limit: Option<u64>,
+    position: u64,
+    keystream: Option<(u64, Vec<u8>)>,
+    _p: PhantomData<W>,
+}
+
+impl<W: Word, C: EncodeBlock<W>> Ctr<W, C> {
+    /// `Error` - if `nonce` doesn't leave at least one byte of the block for the counter
+    pub fn new(cipher: C, nonce: &[u8]) -> Result<Self, Error> {
+        if nonce.len() >= W::BLOCK_BYTES {
+            return Err(Error::WrongNonceSize);
+        }
+        let counter_bits = 8 * (W::BLOCK_BYTES - nonce.len()) as u32;
+
+        Ok(Self {
+            cipher,
+            nonce: nonce.to_vec(),
+            blocks_limit: 1u64.checked_shl(counter_bits),
+            position: 0,
+            keystream: None,
+            _p: PhantomData::default(),
+        })
+    }
+
+    /// Current byte offset in the keystream
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Move to `byte_offset` in the keystream
+    /// `Error` - if the counter can't reach this offset
+    pub fn seek(&mut self, byte_offset: u64) -> Result<(), Error> {
+        self.check_limit(byte_offset / W::BLOCK_BYTES as u64)?;
+        self.position = byte_offset;
+        Ok(())
+    }
+
+    /// XOR `input` with the keystream from the current position
+    ///
+    /// `Error` - if the counter overflows before the end of `input`,
+    /// nothing is processed in that case
+    pub fn update(&mut self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        let end = self
+            .position
+            .checked_add(input.len() as u64)
+            .ok_or(Error::CounterOverflow)?;
+        if end != self.position {
+            self.check_limit((end - 1) / W::BLOCK_BYTES as u64)?;
+        }
+
+        let mut output = Vec::with_capacity(input.len());
+        for byte in input {
+            let offset = (self.position % W::BLOCK_BYTES as u64) as usize;
+            output.push(byte ^ self.keystream_block()[offset]);
+            self.position += 1;
+        }
+        Ok(output)
+    }
+
+    /// One-shot processing of the whole `input` from the start of the keystream
+    /// `Error` - if the counter overflows before the end of `input`
+    pub fn process(mut self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        self.seek(0)?;
+        self.update(input)
+    }
+
+    fn check_limit(&self, block_index: u64) -> Result<(), Error> {
+        match self.blocks_limit {
+            Some(limit) if block_index >= limit => Err(Error::CounterOverflow),
+            _ => Ok(()),
+        }
+    }
+
+    /// Keystream block for the current position, cached until the position leaves it
+    fn keystream_block(&mut self) -> &[u8] {
+        let index = self.position / W::BLOCK_BYTES as u64;
+        if !matches!(&self.keystream, Some((cached, _)) if *cached == index) {
+            let mut counter_block = self.nonce.clone();
+            counter_block.resize(W::BLOCK_BYTES, 0x00);
+            // The counter is checked to fit, so only leading zeros are cut
+            let counter = index.to_be_bytes();
+            let len = counter.len().min(W::BLOCK_BYTES - self.nonce.len());
+            counter_block[W::BLOCK_BYTES - len..].copy_from_slice(&counter[counter.len() - len..]);
+
+            let mut keystream = vec![0x00; W::BLOCK_BYTES];
+            write_block(
+                self.cipher.encode_block(read_block(&counter_block)),
+                &mut keystream,
+            );
+            self.keystream = Some((index, keystream));
+        }
+        &self.keystream.as_ref().expect("Filled above").1
+    }
+}
+
+impl<W: Word, C: EncodeBlock<W>> StreamMode for Ctr<W, C> {
+    fn update(&mut self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        Ctr::update(self, input)
+    }
+
+    fn finalize(self) -> Result<Vec<u8>, Error> {
+        Ok(vec![])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schedule::KeySchedule, settings::Rc5Settings};
+
+    #[test]
+    fn test_vectors() {
+        let key: [u8; 16] = (0..16).collect::<Vec<_>>().try_into().unwrap();
+
+        let schedule = KeySchedule::new(key, Rc5Settings::default());
+        let pt = (0..20).collect::<Vec<u8>>();
+        let ct = hex::decode("6cf8a8079ee8e5af9ede962007ee4f4ebac8c0e2").unwrap();
+        let nonce = [0xf0, 0xf1, 0xf2, 0xf3];
+        assert_eq!(
+            Ctr::new(&schedule, &nonce).unwrap().process(&pt),
+            Ok(ct.clone())
+        );
+        assert_eq!(Ctr::new(&schedule, &nonce).unwrap().process(&ct), Ok(pt));
+
+        let schedule = KeySchedule::new(key, Rc5Settings::<u64>::new(16));
+        let pt = (0..40).collect::<Vec<u8>>();
+        let ct = hex::decode(
+            "cf09302da47478ecf9d71d785ede42d0a1fc295ada133d2395a444941ed67534f2c2bbe364484eb3",
+        )
+        .unwrap();
+        let nonce = (0xa0..0xa8).collect::<Vec<u8>>();
+        assert_eq!(Ctr::new(&schedule, &nonce).unwrap().process(&pt), Ok(ct));
+
+        let schedule = KeySchedule::new([0x00, 0x01, 0x02, 0x03], Rc5Settings::<u16>::new(12));
+        let pt = (0..10).collect::<Vec<u8>>();
+        let ct = hex::decode("0453eca848a867347dd7").unwrap();
+        assert_eq!(Ctr::new(&schedule, &[]).unwrap().process(&pt), Ok(ct));
+    }
+
+    #[test]
+    fn test_streaming_and_seek() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u32>::new(12));
+        let nonce = [0x01, 0x02, 0x03];
+        let pt = (0..=255).collect::<Vec<u8>>();
+        let ct = Ctr::new(&schedule, &nonce).unwrap().process(&pt).unwrap();
+
+        for step in [1, 3, 8, 100] {
+            let mut ctr = Ctr::new(&schedule, &nonce).unwrap();
+            let mut encoded = vec![];
+            for chunk in pt.chunks(step) {
+                encoded.extend(ctr.update(chunk).unwrap());
+            }
+            assert_eq!(encoded, ct, "step {step}");
+            assert_eq!(ctr.position(), 256);
+        }
+
+        let mut ctr = Ctr::new(&schedule, &nonce).unwrap();
+        for offset in [200, 13, 0, 255, 64] {
+            ctr.seek(offset).unwrap();
+            assert_eq!(
+                ctr.update(&ct[offset as usize..]).unwrap(),
+                pt[offset as usize..],
+                "offset {offset}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_conformance() {
+        use crate::mode::conformance;
+
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u128>::new(12));
+        let ctr = || Ctr::new(&schedule, &[0x24; 12]).unwrap();
+        conformance::check_stream_mode::<u128, _, _>(ctr, ctr);
+    }
+
+    #[test]
+    fn test_counter_overflow() {
+        let schedule = KeySchedule::new([0x42; 4], Rc5Settings::<u8>::new(12));
+        // One byte of the two bytes block is left for the counter: 256 blocks
+        let mut ctr = Ctr::new(&schedule, &[0x99]).unwrap();
+        assert_eq!(ctr.seek(512), Err(Error::CounterOverflow));
+        ctr.seek(510).unwrap();
+        assert_eq!(ctr.update(&[0x00; 3]), Err(Error::CounterOverflow));
+        assert_eq!(ctr.position(), 510);
+        assert_eq!(ctr.update(&[0x00; 2]).unwrap().len(), 2);
+        assert_eq!(ctr.update(&[]), Ok(vec![]));
+        assert_eq!(ctr.update(&[0x00]), Err(Error::CounterOverflow));
+
+        let schedule = KeySchedule::new([0x42; 4], Rc5Settings::<u64>::new(12));
+        let mut ctr = Ctr::new(&schedule, &[0x99; 8]).unwrap();
+        ctr.seek(u64::MAX).unwrap();
+        assert_eq!(ctr.update(&[0x00]), Err(Error::CounterOverflow));
+    }
+
+    #[test]
+    fn test_wrong_nonce() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::default());
+        assert!(matches!(
+            Ctr::new(&schedule, &[0x00; 8]),
+            Err(Error::WrongNonceSize)
+        ));
+        assert!(Ctr::new(&schedule, &[0x00; 7]).is_ok());
+    }
+}
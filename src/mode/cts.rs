@@ -0,0 +1,382 @@
+use crate::{
+    block::{append_block, read_block},
+    cipher::{DecodeBlock, EncodeBlock},
+    mode::{process_buffered, read_iv, BlockBuffer, BlockMode, CbcDecoder, CbcEncoder, StreamMode},
+    word::Word,
+    Error,
+};
+
+/// Order of the last two ciphertext blocks in ciphertext stealing,
+/// as defined by the addendum to NIST SP 800-38A
+///
+/// The last plaintext block `P_n*` of `d` bytes is padded with zeroes and
+/// encoded in CBC mode, after that the penultimate ciphertext block `C_{n-1}`
+/// is truncated to `d` bytes, so the output has the same length as the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CtsVariant {
+    /// `.. C_{n-2} | C_{n-1}* | C_n`, never swapped
+    Cs1,
+    /// Same as [`CtsVariant::Cs1`] if the last block is complete,
+    /// otherwise same as [`CtsVariant::Cs3`]
+    Cs2,
+    /// `.. C_{n-2} | C_n | C_{n-1}*`, always swapped
+    Cs3,
+}
+
+impl CtsVariant {
+    /// RC5-CTS from [RFC 2040](https://www.rfc-editor.org/rfc/rfc2040#section-8):
+    /// the last two blocks are always swapped, same as [`CtsVariant::Cs3`]
+    pub const RFC2040: Self = Self::Cs3;
+
+    fn swaps<W: Word>(self, last_block_len: usize) -> bool {
+        match self {
+            Self::Cs1 => false,
+            Self::Cs2 => last_block_len != W::BLOCK_BYTES,
+            Self::Cs3 => true,
+        }
+    }
+}
+
+/// RC5 in CBC mode with ciphertext stealing, encryption side
+///
+/// The output has the same length as the input, which has
+/// to be at least one block long. All blocks except the last
+/// two ones are the same as in [`CbcEncoder`]
+pub struct CtsEncoder<W: Word, C: EncodeBlock<W>> {
+    cbc: CbcEncoder<W, C>,
+    buffer: BlockBuffer<W>,
+    variant: CtsVariant,
+    len: usize,
+}
+
+impl<W: Word, C: EncodeBlock<W>> CtsEncoder<W, C> {
+    /// `Error` - if `iv` is not exactly one block long
+    pub fn new(cipher: C, iv: &[u8], variant: CtsVariant) -> Result<Self, Error> {
+        Ok(Self {
+            cbc: CbcEncoder::init(cipher, [read_iv(iv)?]),
+            buffer: BlockBuffer::default(),
+            variant,
+            len: 0,
+        })
+    }
+
+    /// Encode all complete blocks available so far,
+    /// except the last two ones
+    pub fn update(&mut self, input: &[u8]) -> Vec<u8> {
+        self.len += input.len();
+        self.process(input, 2 * W::BLOCK_BYTES)
+    }
+
+    /// Encode the last two blocks
+    /// `Error` - if the total input is shorter than one block
+    pub fn finalize(mut self) -> Result<Vec<u8>, Error> {
+        if self.len < W::BLOCK_BYTES {
+            return Err(Error::WrongInputSize);
+        }
+        let last_block_len = (self.len - 1) % W::BLOCK_BYTES + 1;
+        let mut output = self.process(&vec![0x00; W::BLOCK_BYTES - last_block_len], 0);
+        if self.len == W::BLOCK_BYTES {
+            return Ok(output);
+        }
+
+        let last = output.split_off(output.len() - W::BLOCK_BYTES);
+        let penultimate = output.split_off(output.len() - W::BLOCK_BYTES);
+        match self.variant.swaps::<W>(last_block_len) {
+            true => {
+                output.extend_from_slice(&last);
+                output.extend_from_slice(&penultimate[..last_block_len]);
+            }
+            false => {
+                output.extend_from_slice(&penultimate[..last_block_len]);
+                output.extend_from_slice(&last);
+            }
+        }
+        Ok(output)
+    }
+
+    /// One-shot encoding of the whole `input`
+    /// `Error` - if `input` is shorter than one block
+    pub fn encode(mut self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut output = self.update(input);
+        output.extend(self.finalize()?);
+        Ok(output)
+    }
+
+    fn process(&mut self, input: &[u8], hold: usize) -> Vec<u8> {
+        process_buffered(&mut self.cbc, &mut self.buffer, input, hold)
+    }
+}
+
+impl<W: Word, C: EncodeBlock<W>> StreamMode for CtsEncoder<W, C> {
+    fn update(&mut self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(CtsEncoder::update(self, input))
+    }
+
+    fn finalize(self) -> Result<Vec<u8>, Error> {
+        CtsEncoder::finalize(self)
+    }
+}
+
+/// RC5 in CBC mode with ciphertext stealing, decryption side
+///
+/// Check [`CtsEncoder`] for details
+pub struct CtsDecoder<W: Word, C: DecodeBlock<W>> {
+    cbc: CbcDecoder<W, C>,
+    buffer: BlockBuffer<W>,
+    variant: CtsVariant,
+    len: usize,
+}
+
+impl<W: Word, C: DecodeBlock<W>> CtsDecoder<W, C> {
+    /// `Error` - if `iv` is not exactly one block long
+    pub fn new(cipher: C, iv: &[u8], variant: CtsVariant) -> Result<Self, Error> {
+        Ok(Self {
+            cbc: CbcDecoder::init(cipher, [read_iv(iv)?]),
+            buffer: BlockBuffer::default(),
+            variant,
+            len: 0,
+        })
+    }
+
+    /// Decode all complete blocks available so far,
+    /// except the last two ones
+    pub fn update(&mut self, input: &[u8]) -> Vec<u8> {
+        self.len += input.len();
+        self.process(input, 2 * W::BLOCK_BYTES)
+    }
+
+    /// Decode the last two blocks
+    /// `Error` - if the total input is shorter than one block
+    pub fn finalize(mut self) -> Result<Vec<u8>, Error> {
+        if self.len < W::BLOCK_BYTES {
+            return Err(Error::WrongInputSize);
+        }
+        if self.len == W::BLOCK_BYTES {
+            return Ok(self.process(&[], 0));
+        }
+
+        let last_block_len = (self.len - 1) % W::BLOCK_BYTES + 1;
+        let mut output = self.process(&[], W::BLOCK_BYTES + last_block_len);
+        let tail = self.buffer.take();
+        let (stolen, last) = match self.variant.swaps::<W>(last_block_len) {
+            true => {
+                let (last, stolen) = tail.split_at(W::BLOCK_BYTES);
+                (stolen, last)
+            }
+            false => tail.split_at(last_block_len),
+        };
+
+        // The zero padding of `P_n` makes the tail of `D(C_n)`
+        // equal to the truncated tail of `C_{n-1}`
+        let mut decoded_last = Vec::with_capacity(W::BLOCK_BYTES);
+        append_block(
+            &mut decoded_last,
+            self.cbc.cipher().decode_block(read_block(last)),
+        );
+        let mut penultimate = stolen.to_vec();
+        penultimate.extend_from_slice(&decoded_last[last_block_len..]);
+        penultimate.extend_from_slice(last);
+
+        output.extend(self.process(&penultimate, 0));
+        output.truncate(output.len() - (W::BLOCK_BYTES - last_block_len));
+        Ok(output)
+    }
+
+    /// One-shot decoding of the whole `input`
+    /// `Error` - if `input` is shorter than one block
+    pub fn decode(mut self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut output = self.update(input);
+        output.extend(self.finalize()?);
+        Ok(output)
+    }
+
+    fn process(&mut self, input: &[u8], hold: usize) -> Vec<u8> {
+        process_buffered(&mut self.cbc, &mut self.buffer, input, hold)
+    }
+}
+
+impl<W: Word, C: DecodeBlock<W>> StreamMode for CtsDecoder<W, C> {
+    fn update(&mut self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(CtsDecoder::update(self, input))
+    }
+
+    fn finalize(self) -> Result<Vec<u8>, Error> {
+        CtsDecoder::finalize(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schedule::KeySchedule, settings::Rc5Settings};
+
+    const VARIANTS: [CtsVariant; 3] = [CtsVariant::Cs1, CtsVariant::Cs2, CtsVariant::Cs3];
+
+    /// RFC 2040 has no vectors for RC5-CTS
+    #[test]
+    fn test_vectors() {
+        let key: [u8; 16] = (0..16).collect::<Vec<_>>().try_into().unwrap();
+        let schedule = KeySchedule::new(key, Rc5Settings::default());
+        let iv = (8..16).collect::<Vec<u8>>();
+
+        for (len, expected) in [
+            (8, ["f8962e45b82a28c9"; 3]),
+            (
+                9,
+                [
+                    "f8184b9df8262a757c",
+                    "184b9df8262a757cf8",
+                    "184b9df8262a757cf8",
+                ],
+            ),
+            (
+                15,
+                [
+                    "f8962e45b82a2816f8465d1a75c921",
+                    "16f8465d1a75c921f8962e45b82a28",
+                    "16f8465d1a75c921f8962e45b82a28",
+                ],
+            ),
+            (
+                16,
+                [
+                    "f8962e45b82a28c94c8f12d8afc502d9",
+                    "f8962e45b82a28c94c8f12d8afc502d9",
+                    "4c8f12d8afc502d9f8962e45b82a28c9",
+                ],
+            ),
+            (
+                17,
+                [
+                    "f8962e45b82a28c94cd61b9de3745c8524",
+                    "f8962e45b82a28c9d61b9de3745c85244c",
+                    "f8962e45b82a28c9d61b9de3745c85244c",
+                ],
+            ),
+            (
+                23,
+                [
+                    "f8962e45b82a28c94c8f12d8afc502e06e88c56ff456c3",
+                    "f8962e45b82a28c9e06e88c56ff456c34c8f12d8afc502",
+                    "f8962e45b82a28c9e06e88c56ff456c34c8f12d8afc502",
+                ],
+            ),
+        ] {
+            let pt = (0x20..0x20 + len).collect::<Vec<u8>>();
+            for (variant, ct) in VARIANTS.into_iter().zip(expected) {
+                let ct = hex::decode(ct).unwrap();
+                assert_eq!(
+                    CtsEncoder::new(&schedule, &iv, variant)
+                        .unwrap()
+                        .encode(&pt),
+                    Ok(ct.clone()),
+                    "len {len}, {variant:?}"
+                );
+                assert_eq!(
+                    CtsDecoder::new(&schedule, &iv, variant)
+                        .unwrap()
+                        .decode(&ct),
+                    Ok(pt.clone()),
+                    "len {len}, {variant:?}"
+                );
+            }
+        }
+
+        let schedule = KeySchedule::new(key, Rc5Settings::<u64>::new(16));
+        let iv = (0..16).collect::<Vec<u8>>();
+        let pt = (0x40..0x40 + 37).collect::<Vec<u8>>();
+        let ct = hex::decode(
+            "bdf6d1ffaaeca6c5339cf1492b8cccfc5fa0b8c89fd0df296755d8fed4eda02b0f1e7b8a9e",
+        )
+        .unwrap();
+        assert_eq!(
+            CtsEncoder::new(&schedule, &iv, CtsVariant::RFC2040)
+                .unwrap()
+                .encode(&pt),
+            Ok(ct.clone())
+        );
+        assert_eq!(
+            CtsDecoder::new(&schedule, &iv, CtsVariant::RFC2040)
+                .unwrap()
+                .decode(&ct),
+            Ok(pt)
+        );
+    }
+
+    #[test]
+    fn test_cs1_aligned_is_cbc() {
+        let schedule = KeySchedule::new([0x33; 10], Rc5Settings::<u16>::new(12));
+        let iv = [0x01, 0x02, 0x03, 0x04];
+        let pt = (0..24).collect::<Vec<u8>>();
+        assert_eq!(
+            CtsEncoder::new(&schedule, &iv, CtsVariant::Cs1)
+                .unwrap()
+                .encode(&pt),
+            CbcEncoder::new(&schedule, &iv).unwrap().process_all(&pt)
+        );
+    }
+
+    #[test]
+    fn test_length_preserving() {
+        let schedule = KeySchedule::new([0x5a; 16], Rc5Settings::<u32>::new(12));
+        let iv = [0x77; 8];
+        for len in 8..40 {
+            let pt = (0..len).collect::<Vec<u8>>();
+            for variant in VARIANTS {
+                let ct = CtsEncoder::new(&schedule, &iv, variant)
+                    .unwrap()
+                    .encode(&pt)
+                    .unwrap();
+                assert_eq!(ct.len(), pt.len());
+
+                for step in [1, 5, 8, 13] {
+                    let mut encoder = CtsEncoder::new(&schedule, &iv, variant).unwrap();
+                    let mut decoder = CtsDecoder::new(&schedule, &iv, variant).unwrap();
+                    let (mut encoded, mut decoded) = (vec![], vec![]);
+                    for chunk in pt.chunks(step) {
+                        encoded.extend(encoder.update(chunk));
+                    }
+                    encoded.extend(encoder.finalize().unwrap());
+                    for chunk in ct.chunks(step) {
+                        decoded.extend(decoder.update(chunk));
+                    }
+                    decoded.extend(decoder.finalize().unwrap());
+
+                    assert_eq!(encoded, ct, "len {len}, step {step}, {variant:?}");
+                    assert_eq!(decoded, pt, "len {len}, step {step}, {variant:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_conformance() {
+        use crate::mode::conformance;
+
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u32>::new(12));
+        let iv = [0x24; 8];
+        for variant in VARIANTS {
+            conformance::check_stream_mode::<u32, _, _>(
+                || CtsEncoder::new(&schedule, &iv, variant).unwrap(),
+                || CtsDecoder::new(&schedule, &iv, variant).unwrap(),
+            );
+        }
+    }
+
+    #[test]
+    fn test_short_input() {
+        let schedule = KeySchedule::new([0x5a; 16], Rc5Settings::default());
+        assert_eq!(
+            CtsEncoder::new(&schedule, &[0x00; 8], CtsVariant::RFC2040)
+                .unwrap()
+                .encode(&[0x00; 7]),
+            Err(Error::WrongInputSize)
+        );
+        assert_eq!(
+            CtsDecoder::new(&schedule, &[0x00; 8], CtsVariant::RFC2040)
+                .unwrap()
+                .decode(&[]),
+            Err(Error::WrongInputSize)
+        );
+    }
+}
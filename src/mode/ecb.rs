@@ -0,0 +1,97 @@
+use std::marker::PhantomData;
+
+use crate::{
+    cipher::{DecodeBlock, EncodeBlock},
+    mode::{BlockMode, Direction},
+    word::Word,
+};
+
+/// RC5 in Electronic Codebook mode, encryption side
+///
+/// Every block is encoded independently, as [`crate::EncodeRc5`] does.
+/// It has no IV and is meant for [`crate::ModeStream`]:
+/// streaming and padding of the ECB-style API
+pub struct EcbEncoder<W: Word, C: EncodeBlock<W>> {
+    cipher: C,
+    _p: PhantomData<W>,
+}
+
+impl<W: Word, C: EncodeBlock<W>> BlockMode<W> for EcbEncoder<W, C> {
+    type Cipher = C;
+    type Iv = [(W, W); 0];
+    const DIRECTION: Direction = Direction::Encode;
+
+    fn init(cipher: C, _iv: [(W, W); 0]) -> Self {
+        Self {
+            cipher,
+            _p: PhantomData::default(),
+        }
+    }
+
+    fn process_block(&mut self, block: (W, W)) -> (W, W) {
+        self.cipher.encode_block(block)
+    }
+}
+
+/// RC5 in Electronic Codebook mode, decryption side
+///
+/// Check [`EcbEncoder`] for details
+pub struct EcbDecoder<W: Word, C: DecodeBlock<W>> {
+    cipher: C,
+    _p: PhantomData<W>,
+}
+
+impl<W: Word, C: DecodeBlock<W>> BlockMode<W> for EcbDecoder<W, C> {
+    type Cipher = C;
+    type Iv = [(W, W); 0];
+    const DIRECTION: Direction = Direction::Decode;
+
+    fn init(cipher: C, _iv: [(W, W); 0]) -> Self {
+        Self {
+            cipher,
+            _p: PhantomData::default(),
+        }
+    }
+
+    fn process_block(&mut self, block: (W, W)) -> (W, W) {
+        self.cipher.decode_block(block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mode::{conformance, ModeStream},
+        schedule::KeySchedule,
+        settings::Rc5Settings,
+        EncodeRc5,
+    };
+
+    #[test]
+    fn test_same_as_encode_rc5() {
+        let key = [0x42; 16];
+        let schedule = KeySchedule::new(key, Rc5Settings::default());
+        let pt = (0..32).collect::<Vec<u8>>();
+        assert_eq!(
+            ModeStream::<_, EcbEncoder<_, _>>::init(&schedule, &[])
+                .unwrap()
+                .process_all(&pt),
+            pt.encode_rc5(key)
+        );
+    }
+
+    #[test]
+    fn test_conformance() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u32>::new(12));
+        conformance::check_block_mode::<_, EcbEncoder<_, _>, EcbDecoder<_, _>>(
+            || &schedule,
+            || &schedule,
+        );
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u8>::new(12));
+        conformance::check_block_mode::<_, EcbEncoder<_, _>, EcbDecoder<_, _>>(
+            || &schedule,
+            || &schedule,
+        );
+    }
+}
@@ -0,0 +1,251 @@
+use crate::{
+    block::xor_block,
+    cipher::{DecodeBlock, EncodeBlock},
+    mode::{read_iv, BlockMode, Direction, ModeStream},
+    word::Word,
+    Error,
+};
+
+/// RC5 in Infinite Garble Extension mode, encryption side
+///
+/// `y_i = E(x_i ^ y_{i-1}) ^ x_{i-1}`, where `x` are the plaintext blocks
+/// and `y` are the ciphertext ones. So the IV is two blocks: the plaintext `x0`
+/// and the ciphertext `y0` "before" the message.
+/// A changed ciphertext block garbles all plaintext blocks after it.
+///
+/// The input must be a multiple of the block size. [`IgeEncoder::new`] gives
+/// a [`ModeStream`]: use [`ModeStream::update`] for streaming
+/// or [`ModeStream::process_all`] for one-shot
+pub struct IgeEncoder<W: Word, C: EncodeBlock<W>> {
+    cipher: C,
+    previous_x: (W, W),
+    previous_y: (W, W),
+}
+
+impl<W: Word, C: EncodeBlock<W>> IgeEncoder<W, C> {
+    /// `Error` - if `x0` or `y0` is not exactly one block long
+    pub fn new(cipher: C, x0: &[u8], y0: &[u8]) -> Result<ModeStream<W, Self>, Error> {
+        Ok(ModeStream::new(cipher, [read_iv(x0)?, read_iv(y0)?]))
+    }
+}
+
+/// The IV is `x0 | y0`
+impl<W: Word, C: EncodeBlock<W>> BlockMode<W> for IgeEncoder<W, C> {
+    type Cipher = C;
+    type Iv = [(W, W); 2];
+    const DIRECTION: Direction = Direction::Encode;
+
+    fn init(cipher: C, [previous_x, previous_y]: [(W, W); 2]) -> Self {
+        Self {
+            cipher,
+            previous_x,
+            previous_y,
+        }
+    }
+
+    fn process_block(&mut self, x: (W, W)) -> (W, W) {
+        let y = xor_block(
+            self.cipher.encode_block(xor_block(x, self.previous_y)),
+            self.previous_x,
+        );
+        (self.previous_x, self.previous_y) = (x, y);
+        y
+    }
+}
+
+/// RC5 in Infinite Garble Extension mode, decryption side
+///
+/// `x_i = D(y_i ^ x_{i-1}) ^ y_{i-1}`, check [`IgeEncoder`] for details
+pub struct IgeDecoder<W: Word, C: DecodeBlock<W>> {
+    cipher: C,
+    previous_x: (W, W),
+    previous_y: (W, W),
+}
+
+impl<W: Word, C: DecodeBlock<W>> IgeDecoder<W, C> {
+    /// `Error` - if `x0` or `y0` is not exactly one block long
+    pub fn new(cipher: C, x0: &[u8], y0: &[u8]) -> Result<ModeStream<W, Self>, Error> {
+        Ok(ModeStream::new(cipher, [read_iv(x0)?, read_iv(y0)?]))
+    }
+}
+
+/// The IV is `x0 | y0`
+impl<W: Word, C: DecodeBlock<W>> BlockMode<W> for IgeDecoder<W, C> {
+    type Cipher = C;
+    type Iv = [(W, W); 2];
+    const DIRECTION: Direction = Direction::Decode;
+
+    fn init(cipher: C, [previous_x, previous_y]: [(W, W); 2]) -> Self {
+        Self {
+            cipher,
+            previous_x,
+            previous_y,
+        }
+    }
+
+    fn process_block(&mut self, y: (W, W)) -> (W, W) {
+        let x = xor_block(
+            self.cipher.decode_block(xor_block(y, self.previous_x)),
+            self.previous_y,
+        );
+        (self.previous_x, self.previous_y) = (x, y);
+        x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schedule::KeySchedule, settings::Rc5Settings};
+
+    fn check<W: Word>(schedule: &KeySchedule<W>, x0: &[u8], y0: &[u8], ct: &str) {
+        let ct = hex::decode(ct).unwrap();
+        let pt = (0..ct.len() as u8).collect::<Vec<u8>>();
+        assert_eq!(
+            IgeEncoder::new(schedule, x0, y0).unwrap().process_all(&pt),
+            Ok(ct.clone())
+        );
+        assert_eq!(
+            IgeDecoder::new(schedule, x0, y0).unwrap().process_all(&ct),
+            Ok(pt)
+        );
+    }
+
+    #[test]
+    fn test_vectors() {
+        let key: [u8; 16] = (0..16).collect::<Vec<_>>().try_into().unwrap();
+        let iv = (0..64).collect::<Vec<u8>>();
+
+        check(
+            &KeySchedule::new(key, Rc5Settings::default()),
+            &iv[..8],
+            &iv[8..16],
+            "5c8238119af76506f116aa3f60456dd4573540e4263671ab",
+        );
+        check(
+            &KeySchedule::new(key, Rc5Settings::<u64>::new(16)),
+            &iv[..16],
+            &iv[16..32],
+            "20b2157e757bf5aa691bd507d93de0097624b52956020391\
+             b88b581146b781a57d06199bc548d77e29c6af95caeea098",
+        );
+
+        let key = [0x00, 0x01, 0x02, 0x03];
+        check(
+            &KeySchedule::new(key, Rc5Settings::<u8>::new(12)),
+            &[0x01, 0x02],
+            &[0x03, 0x04],
+            "7df84e8b6eef4941e5fe",
+        );
+        check(
+            &KeySchedule::new(key, Rc5Settings::<u128>::new(12)),
+            &iv[..32],
+            &iv[32..],
+            "a64832174a5c2a6f2926f754b47cb92b7f3cc1f5f5415d12ed24315d7488f484\
+             6f6f8f73b04925cb25312a25aa0f19bec4429f1bbf75563fdc284ce385df166f",
+        );
+    }
+
+    #[test]
+    fn test_incremental() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u16>::new(16));
+        let (x0, y0) = ([0x01; 4], [0x02; 4]);
+        let pt = (0..100).collect::<Vec<u8>>();
+        let ct = IgeEncoder::new(&schedule, &x0, &y0)
+            .unwrap()
+            .process_all(&pt)
+            .unwrap();
+
+        for step in [1, 3, 4, 64] {
+            let mut encoder = IgeEncoder::new(&schedule, &x0, &y0).unwrap();
+            let mut decoder = IgeDecoder::new(&schedule, &x0, &y0).unwrap();
+            let (mut encoded, mut decoded) = (vec![], vec![]);
+            for chunk in pt.chunks(step) {
+                encoded.extend(encoder.update(chunk));
+            }
+            encoded.extend(encoder.finalize().unwrap());
+            for chunk in ct.chunks(step) {
+                decoded.extend(decoder.update(chunk));
+            }
+            decoded.extend(decoder.finalize().unwrap());
+            assert_eq!(encoded, ct, "step {step}");
+            assert_eq!(decoded, pt, "step {step}");
+        }
+    }
+
+    #[test]
+    fn test_conformance() {
+        use crate::mode::conformance;
+
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u8>::new(12));
+        conformance::check_block_mode::<_, IgeEncoder<_, _>, IgeDecoder<_, _>>(
+            || &schedule,
+            || &schedule,
+        );
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u32>::new(12));
+        conformance::check_block_mode::<_, IgeEncoder<_, _>, IgeDecoder<_, _>>(
+            || &schedule,
+            || &schedule,
+        );
+    }
+
+    #[test]
+    fn test_infinite_garble() {
+        fn check<W: Word>(settings: Rc5Settings<W>) {
+            let schedule = KeySchedule::new([0x42; 16], settings);
+            let iv = vec![0x24; 2 * W::BLOCK_BYTES];
+            let (x0, y0) = iv.split_at(W::BLOCK_BYTES);
+            let pt = vec![0x00; 6 * W::BLOCK_BYTES];
+            let mut ct = IgeEncoder::new(&schedule, x0, y0)
+                .unwrap()
+                .process_all(&pt)
+                .unwrap();
+
+            // One flipped bit of the third block garbles it and all blocks after it
+            ct[2 * W::BLOCK_BYTES] ^= 0x01;
+            let decoded = IgeDecoder::new(&schedule, x0, y0)
+                .unwrap()
+                .process_all(&ct)
+                .unwrap();
+            let blocks = decoded
+                .chunks(W::BLOCK_BYTES)
+                .zip(pt.chunks(W::BLOCK_BYTES));
+            for (index, (decoded, pt)) in blocks.enumerate() {
+                match index < 2 {
+                    true => assert_eq!(decoded, pt, "block {index}"),
+                    false => assert_ne!(decoded, pt, "block {index}"),
+                }
+            }
+        }
+
+        check(Rc5Settings::<u16>::new(12));
+        check(Rc5Settings::<u32>::new(12));
+        check(Rc5Settings::<u64>::new(16));
+        check(Rc5Settings::<u128>::new(20));
+    }
+
+    #[test]
+    fn test_wrong_input() {
+        let schedule = KeySchedule::new([0x01; 16], Rc5Settings::default());
+        assert!(matches!(
+            IgeEncoder::new(&schedule, &[0x00; 8], &[0x00; 16]),
+            Err(Error::WrongIvSize)
+        ));
+        assert!(matches!(
+            IgeDecoder::new(&schedule, &[0x00; 7], &[0x00; 8]),
+            Err(Error::WrongIvSize)
+        ));
+        assert_eq!(
+            IgeEncoder::new(&schedule, &[0x00; 8], &[0x00; 8])
+                .unwrap()
+                .process_all(&[0x00; 9]),
+            Err(Error::WrongInputSize)
+        );
+        assert_eq!(
+            IgeDecoder::new(&schedule, &[0x00; 8], &[0x00; 8])
+                .unwrap()
+                .process_all(&[0x00; 4]),
+            Err(Error::WrongInputSize)
+        );
+    }
+}
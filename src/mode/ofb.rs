@@ -0,0 +1,179 @@
+use crate::{
+    block::write_block,
+    cipher::EncodeBlock,
+    mode::{read_iv, IvGuard, StreamMode},
+    word::Word,
+    Error,
+};
+
+/// RC5 in Output Feedback mode
+///
+/// The keystream is the initialization vector encoded again and again,
+/// so it doesn't depend on the data and a flipped bit of the ciphertext
+/// flips only the same bit of the plaintext. Encoding and decoding are the same operation.
+///
+/// The same IV must never be used twice with the same key: the keystream repeats.
+/// [`Ofb::with_guard`] can check it with an [`IvGuard`]
+pub struct Ofb<W: Word, C: EncodeBlock<W>> {
+    cipher: C,
+    register: (W, W),
+    keystream: Vec<u8>,
+    /// Bytes of `keystream` already used
+    used: usize,
+}
+
+impl<W: Word, C: EncodeBlock<W>> Ofb<W, C> {
+    /// `Error` - if `iv` is not exactly one block long
+    pub fn new(cipher: C, iv: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            cipher,
+            register: read_iv(iv)?,
+            keystream: vec![0x00; W::BLOCK_BYTES],
+            used: W::BLOCK_BYTES,
+        })
+    }
+
+    /// Same as [`Ofb::new`], but `iv` is registered in `guard` first
+    ///
+    /// `Error::IvReused` - if `guard` has already seen `iv`
+    pub fn with_guard(cipher: C, iv: &[u8], guard: &mut IvGuard) -> Result<Self, Error> {
+        let ofb = Self::new(cipher, iv)?;
+        guard.check(iv)?;
+        Ok(ofb)
+    }
+
+    /// XOR `input` with the keystream, it can be of any length
+    pub fn update(&mut self, input: &[u8]) -> Vec<u8> {
+        let mut output = Vec::with_capacity(input.len());
+        for byte in input {
+            if self.used == W::BLOCK_BYTES {
+                self.register = self.cipher.encode_block(self.register);
+                write_block(self.register, &mut self.keystream);
+                self.used = 0;
+            }
+            output.push(byte ^ self.keystream[self.used]);
+            self.used += 1;
+        }
+        output
+    }
+
+    /// One-shot processing of the whole `input`
+    pub fn process(mut self, input: &[u8]) -> Vec<u8> {
+        self.update(input)
+    }
+}
+
+impl<W: Word, C: EncodeBlock<W>> StreamMode for Ofb<W, C> {
+    fn update(&mut self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(Ofb::update(self, input))
+    }
+
+    fn finalize(self) -> Result<Vec<u8>, Error> {
+        Ok(vec![])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schedule::KeySchedule, settings::Rc5Settings};
+
+    #[test]
+    fn test_vectors() {
+        let key: [u8; 16] = (0..16).collect::<Vec<_>>().try_into().unwrap();
+
+        let schedule = KeySchedule::new(key, Rc5Settings::default());
+        let iv = hex::decode("0102030405060708").unwrap();
+        let pt = (0..20).collect::<Vec<u8>>();
+        let ct = hex::decode("734697fb2ebaf7bdf9b7001d0fa7a8142a37ba33").unwrap();
+        assert_eq!(Ofb::new(&schedule, &iv).unwrap().process(&pt), ct);
+        assert_eq!(Ofb::new(&schedule, &iv).unwrap().process(&ct), pt);
+
+        let schedule = KeySchedule::new(key, Rc5Settings::<u64>::new(16));
+        let iv = (16..32).collect::<Vec<u8>>();
+        let pt = (0..40).collect::<Vec<u8>>();
+        let ct = hex::decode(
+            "c70274968396fe15ecb6066654955e82e478f463dbfc9455e63626817a692baa0797fa67a68c17dd",
+        )
+        .unwrap();
+        assert_eq!(Ofb::new(&schedule, &iv).unwrap().process(&pt), ct);
+
+        let schedule = KeySchedule::new([0x00, 0x01, 0x02, 0x03], Rc5Settings::<u8>::new(12));
+        let pt = (0..10).collect::<Vec<u8>>();
+        let ct = hex::decode("5b1fca590aca1715e299").unwrap();
+        assert_eq!(Ofb::new(&schedule, &[0xa0, 0xa1]).unwrap().process(&pt), ct);
+
+        let schedule = KeySchedule::new([0x00, 0x01, 0x02, 0x03], Rc5Settings::<u128>::new(12));
+        let iv = (0..32).collect::<Vec<u8>>();
+        let pt = (0..40).collect::<Vec<u8>>();
+        let ct = hex::decode(
+            "43ef11ea317f80f5f86b322e29546aac77297552024ea97299c990987879e06a817e3d723f5448b4",
+        )
+        .unwrap();
+        assert_eq!(Ofb::new(&schedule, &iv).unwrap().process(&pt), ct);
+    }
+
+    #[test]
+    fn test_streaming() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u16>::new(16));
+        let iv = [0x24; 4];
+        let pt = (0..=100).collect::<Vec<u8>>();
+        let ct = Ofb::new(&schedule, &iv).unwrap().process(&pt);
+
+        for step in [1, 3, 4, 64] {
+            let mut encoder = Ofb::new(&schedule, &iv).unwrap();
+            let mut decoder = Ofb::new(&schedule, &iv).unwrap();
+            let (mut encoded, mut decoded) = (vec![], vec![]);
+            for chunk in pt.chunks(step) {
+                encoded.extend(encoder.update(chunk));
+            }
+            for chunk in ct.chunks(step) {
+                decoded.extend(decoder.update(chunk));
+            }
+            assert_eq!(encoded, ct, "step {step}");
+            assert_eq!(decoded, pt, "step {step}");
+        }
+    }
+
+    #[test]
+    fn test_conformance() {
+        use crate::mode::conformance;
+
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u8>::new(12));
+        let ofb = || Ofb::new(&schedule, &[0x24; 2]).unwrap();
+        conformance::check_stream_mode::<u8, _, _>(ofb, ofb);
+    }
+
+    #[test]
+    fn test_no_error_propagation() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::default());
+        let iv = [0x24; 8];
+        let pt = [0x00; 32];
+        let mut ct = Ofb::new(&schedule, &iv).unwrap().process(&pt);
+        ct[10] ^= 0x80;
+
+        let mut expected = pt;
+        expected[10] ^= 0x80;
+        assert_eq!(Ofb::new(&schedule, &iv).unwrap().process(&ct), expected);
+    }
+
+    #[test]
+    fn test_iv_guard() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::default());
+        let mut guard = IvGuard::default();
+        assert!(Ofb::with_guard(&schedule, &[0x01; 8], &mut guard).is_ok());
+        assert!(Ofb::with_guard(&schedule, &[0x02; 8], &mut guard).is_ok());
+        assert!(matches!(
+            Ofb::with_guard(&schedule, &[0x01; 8], &mut guard),
+            Err(Error::IvReused)
+        ));
+        // A malformed IV is not registered
+        assert!(matches!(
+            Ofb::with_guard(&schedule, &[0x03; 4], &mut guard),
+            Err(Error::WrongIvSize)
+        ));
+        assert!(Ofb::with_guard(&schedule, &[0x03; 8], &mut guard).is_ok());
+        // Without the guard nothing is checked
+        assert!(Ofb::new(&schedule, &[0x01; 8]).is_ok());
+    }
+}
@@ -0,0 +1,273 @@
+use crate::{
+    block::xor_block,
+    cipher::{DecodeBlock, EncodeBlock},
+    mode::{BlockMode, Direction, ModeStream},
+    padding::Padding,
+    word::Word,
+    Error,
+};
+
+/// RC5 in Propagating Cipher Block Chaining mode, encryption side
+///
+/// Same as [`crate::CbcEncoder`], but each plaintext block is XORed with
+/// both the previous plaintext and ciphertext blocks before encoding.
+/// This is the mode of Kerberos v4, a corrupted ciphertext block garbles
+/// all plaintext blocks after it.
+///
+/// [`PcbcEncoder::new`] requires the input to be a multiple of the block size,
+/// [`PcbcEncoder::new_padded`] pads it with the given [`Padding`] scheme.
+/// Both give a [`ModeStream`]
+pub struct PcbcEncoder<W: Word, C: EncodeBlock<W>> {
+    cipher: C,
+    chain: (W, W),
+}
+
+impl<W: Word, C: EncodeBlock<W>> PcbcEncoder<W, C> {
+    /// `Error` - if `iv` is not exactly one block long
+    pub fn new(cipher: C, iv: &[u8]) -> Result<ModeStream<W, Self>, Error> {
+        ModeStream::init(cipher, iv)
+    }
+
+    /// Same as [`PcbcEncoder::new`], but the input
+    /// of any length is padded on finalization
+    pub fn new_padded(
+        cipher: C,
+        iv: &[u8],
+        padding: impl Padding + 'static,
+    ) -> Result<ModeStream<W, Self>, Error> {
+        ModeStream::init_padded(cipher, iv, padding)
+    }
+}
+
+impl<W: Word, C: EncodeBlock<W>> BlockMode<W> for PcbcEncoder<W, C> {
+    type Cipher = C;
+    type Iv = [(W, W); 1];
+    const DIRECTION: Direction = Direction::Encode;
+
+    fn init(cipher: C, [chain]: [(W, W); 1]) -> Self {
+        Self { cipher, chain }
+    }
+
+    fn process_block(&mut self, block: (W, W)) -> (W, W) {
+        let encoded = self.cipher.encode_block(xor_block(block, self.chain));
+        self.chain = xor_block(block, encoded);
+        encoded
+    }
+}
+
+/// RC5 in Propagating Cipher Block Chaining mode, decryption side
+///
+/// Check [`PcbcEncoder`] for details. If the decoder is padded,
+/// the last block is kept until [`ModeStream::finalize`]
+pub struct PcbcDecoder<W: Word, C: DecodeBlock<W>> {
+    cipher: C,
+    chain: (W, W),
+}
+
+impl<W: Word, C: DecodeBlock<W>> PcbcDecoder<W, C> {
+    /// `Error` - if `iv` is not exactly one block long
+    pub fn new(cipher: C, iv: &[u8]) -> Result<ModeStream<W, Self>, Error> {
+        ModeStream::init(cipher, iv)
+    }
+
+    /// Same as [`PcbcDecoder::new`], but the padding
+    /// is removed on finalization
+    pub fn new_padded(
+        cipher: C,
+        iv: &[u8],
+        padding: impl Padding + 'static,
+    ) -> Result<ModeStream<W, Self>, Error> {
+        ModeStream::init_padded(cipher, iv, padding)
+    }
+}
+
+impl<W: Word, C: DecodeBlock<W>> BlockMode<W> for PcbcDecoder<W, C> {
+    type Cipher = C;
+    type Iv = [(W, W); 1];
+    const DIRECTION: Direction = Direction::Decode;
+
+    fn init(cipher: C, [chain]: [(W, W); 1]) -> Self {
+        Self { cipher, chain }
+    }
+
+    fn process_block(&mut self, block: (W, W)) -> (W, W) {
+        let decoded = xor_block(self.cipher.decode_block(block), self.chain);
+        self.chain = xor_block(decoded, block);
+        decoded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{padding::Pkcs7, schedule::KeySchedule, settings::Rc5Settings};
+
+    #[test]
+    fn test_vectors() {
+        let key: [u8; 16] = (0..16).collect::<Vec<_>>().try_into().unwrap();
+
+        let schedule = KeySchedule::new(key, Rc5Settings::default());
+        let iv = hex::decode("0102030405060708").unwrap();
+        let pt = (0..24).collect::<Vec<u8>>();
+        let ct = hex::decode("482a2d0a7bb3a3a5010c28cf64b5a73602dc8def5f11cf15").unwrap();
+        assert_eq!(
+            PcbcEncoder::new(&schedule, &iv).unwrap().process_all(&pt),
+            Ok(ct.clone())
+        );
+        assert_eq!(
+            PcbcDecoder::new(&schedule, &iv).unwrap().process_all(&ct),
+            Ok(pt)
+        );
+
+        let pt = (0..11).collect::<Vec<u8>>();
+        let ct = hex::decode("482a2d0a7bb3a3a5170b297f3d898a3c").unwrap();
+        assert_eq!(
+            PcbcEncoder::new_padded(&schedule, &iv, Pkcs7)
+                .unwrap()
+                .process_all(&pt),
+            Ok(ct.clone())
+        );
+        assert_eq!(
+            PcbcDecoder::new_padded(&schedule, &iv, Pkcs7)
+                .unwrap()
+                .process_all(&ct),
+            Ok(pt)
+        );
+
+        let schedule = KeySchedule::new(key, Rc5Settings::<u64>::new(16));
+        let iv = (16..32).collect::<Vec<u8>>();
+        let pt = (0..48).collect::<Vec<u8>>();
+        let ct = hex::decode(
+            "20b3177d717ef3ad6112df0cd530ee067625b72a52070596b082521a4aba8faa\
+             1f361caba0f24c296a2651c7ee886974",
+        )
+        .unwrap();
+        assert_eq!(
+            PcbcEncoder::new(&schedule, &iv).unwrap().process_all(&pt),
+            Ok(ct)
+        );
+
+        let schedule = KeySchedule::new([0x00, 0x01, 0x02, 0x03], Rc5Settings::<u16>::new(12));
+        let pt = (0..12).collect::<Vec<u8>>();
+        let ct = hex::decode("232012bb881a784552d4e779").unwrap();
+        assert_eq!(
+            PcbcEncoder::new(&schedule, &[0xa0, 0xa1, 0xa2, 0xa3])
+                .unwrap()
+                .process_all(&pt),
+            Ok(ct)
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let schedule = KeySchedule::new([0x0f; 8], Rc5Settings::<u16>::new(16));
+        let iv = [0xa5; 4];
+        for len in 0..20 {
+            let pt = (0..len).collect::<Vec<u8>>();
+            let ct = PcbcEncoder::new_padded(&schedule, &iv, Pkcs7)
+                .unwrap()
+                .process_all(&pt)
+                .unwrap();
+            assert_eq!(ct.len(), (len as usize / 4 + 1) * 4);
+
+            for step in [1, 3, 4, 64] {
+                let mut decoder = PcbcDecoder::new_padded(&schedule, &iv, Pkcs7).unwrap();
+                let mut decoded = vec![];
+                for chunk in ct.chunks(step) {
+                    decoded.extend(decoder.update(chunk));
+                }
+                decoded.extend(decoder.finalize().unwrap());
+                assert_eq!(decoded, pt, "len {len}, step {step}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_conformance() {
+        use crate::mode::conformance;
+
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u16>::new(12));
+        conformance::check_block_mode::<_, PcbcEncoder<_, _>, PcbcDecoder<_, _>>(
+            || &schedule,
+            || &schedule,
+        );
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::<u64>::new(12));
+        conformance::check_block_mode::<_, PcbcEncoder<_, _>, PcbcDecoder<_, _>>(
+            || &schedule,
+            || &schedule,
+        );
+    }
+
+    #[test]
+    fn test_error_propagation() {
+        let schedule = KeySchedule::new([0x42; 16], Rc5Settings::default());
+        let iv = [0x24; 8];
+        let pt = (0..40).collect::<Vec<u8>>();
+        let ct = PcbcEncoder::new(&schedule, &iv)
+            .unwrap()
+            .process_all(&pt)
+            .unwrap();
+
+        // One flipped bit of the second block garbles it and all blocks after it,
+        // unlike CBC, where only the next block is affected
+        let mut corrupted = ct.clone();
+        corrupted[8] ^= 0x01;
+        let decoded = PcbcDecoder::new(&schedule, &iv)
+            .unwrap()
+            .process_all(&corrupted)
+            .unwrap();
+        assert_eq!(decoded[..8], pt[..8]);
+        for (decoded, pt) in decoded.chunks(8).zip(pt.chunks(8)).skip(1) {
+            assert_ne!(decoded, pt);
+        }
+
+        // But two swapped blocks garble only themselves:
+        // the chain after them is the XOR of the same four blocks
+        let mut swapped = ct;
+        swapped[8..24].rotate_left(8);
+        let decoded = PcbcDecoder::new(&schedule, &iv)
+            .unwrap()
+            .process_all(&swapped)
+            .unwrap();
+        assert_eq!(decoded[..8], pt[..8]);
+        assert_ne!(decoded[8..24], pt[8..24]);
+        assert_eq!(decoded[24..], pt[24..]);
+    }
+
+    #[test]
+    fn test_wrong_input() {
+        let schedule = KeySchedule::new([0x01; 16], Rc5Settings::default());
+        let iv = [0x00; 8];
+        assert!(matches!(
+            PcbcEncoder::new(&schedule, &[0x00; 7]),
+            Err(Error::WrongIvSize)
+        ));
+        assert!(matches!(
+            PcbcDecoder::new_padded(&schedule, &[0x00; 9], Pkcs7),
+            Err(Error::WrongIvSize)
+        ));
+        assert_eq!(
+            PcbcEncoder::new(&schedule, &iv)
+                .unwrap()
+                .process_all(&[0x00; 9]),
+            Err(Error::WrongInputSize)
+        );
+        assert_eq!(
+            PcbcDecoder::new_padded(&schedule, &iv, Pkcs7)
+                .unwrap()
+                .process_all(&[]),
+            Err(Error::WrongInputSize)
+        );
+
+        let ct = PcbcEncoder::new(&schedule, &iv)
+            .unwrap()
+            .process_all(&[0x00; 16])
+            .unwrap();
+        assert_eq!(
+            PcbcDecoder::new_padded(&schedule, &iv, Pkcs7)
+                .unwrap()
+                .process_all(&ct),
+            Err(Error::WrongPadding)
+        );
+    }
+}
@@ -0,0 +1,355 @@
+use crate::Error;
+
+/// Padding of the data up to a multiple of the block size
+///
+/// Block modes require block-aligned input: pad the message with [`Padding::pad`]
+/// before encoding and remove the padding with [`Padding::unpad`] after decoding,
+/// or pass the scheme to [`crate::EncodeRc5::encode_rc5_padded`],
+/// [`crate::PcbcEncoder::new_padded`] and the like.
+///
+/// Implement it for your own scheme: only the padding bytes
+/// and the length of the data inside the last block are needed
+pub trait Padding {
+    /// Bytes to append to `data_len` bytes of data,
+    /// so the total length becomes a multiple of `block_len`
+    fn padding(&mut self, block_len: usize, data_len: usize) -> Vec<u8>;
+
+    /// Length of the data inside the padded `last_block`
+    ///
+    /// `last_block` is empty if the whole padded message is empty
+    /// `Error::WrongPadding` - if the padding is malformed
+    fn data_len(&self, last_block: &[u8]) -> Result<usize, Error>;
+
+    /// Pad the whole message
+    fn pad(&mut self, data: &[u8], block_len: usize) -> Vec<u8> {
+        let mut padded = data.to_vec();
+        padded.extend(self.padding(block_len, data.len()));
+        padded
+    }
+
+    /// Remove the padding from the whole message
+    ///
+    /// `Error::WrongInputSize` - if `data` cannot be divided into blocks
+    /// `Error::WrongPadding` - if the padding is malformed
+    fn unpad<'d>(&self, data: &'d [u8], block_len: usize) -> Result<&'d [u8], Error> {
+        if data.len() % block_len != 0 {
+            return Err(Error::WrongInputSize);
+        }
+        let last_block_start = data.len().saturating_sub(block_len);
+        let data_len = self.data_len(&data[last_block_start..])?;
+        Ok(&data[..last_block_start + data_len])
+    }
+}
+
+impl<P: Padding + ?Sized> Padding for &mut P {
+    fn padding(&mut self, block_len: usize, data_len: usize) -> Vec<u8> {
+        (**self).padding(block_len, data_len)
+    }
+
+    fn data_len(&self, last_block: &[u8]) -> Result<usize, Error> {
+        (**self).data_len(last_block)
+    }
+}
+
+impl<P: Padding + ?Sized> Padding for Box<P> {
+    fn padding(&mut self, block_len: usize, data_len: usize) -> Vec<u8> {
+        (**self).padding(block_len, data_len)
+    }
+
+    fn data_len(&self, last_block: &[u8]) -> Result<usize, Error> {
+        (**self).data_len(last_block)
+    }
+}
+
+/// Length of the padding that always adds from 1 to `block_len` bytes
+fn padding_len(block_len: usize, data_len: usize) -> usize {
+    block_len - data_len % block_len
+}
+
+/// `n` bytes of value `n`, as in [RFC 5652](https://www.rfc-editor.org/rfc/rfc5652#section-6.3)
+/// (PKCS#5 for 8 bytes blocks, as in RC5-CBC-Pad)
+///
+/// The padding is always added, unpadding runs in constant time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Pkcs7;
+
+impl Padding for Pkcs7 {
+    fn padding(&mut self, block_len: usize, data_len: usize) -> Vec<u8> {
+        let padding_len = padding_len(block_len, data_len);
+        vec![padding_len as u8; padding_len]
+    }
+
+    /// All bytes of the block are checked regardless
+    /// of where a mismatch is, so the time doesn't depend on it
+    fn data_len(&self, last_block: &[u8]) -> Result<usize, Error> {
+        let padding_len = *last_block.last().ok_or(Error::WrongInputSize)?;
+        let mut invalid =
+            (padding_len == 0) as u8 | (padding_len as usize > last_block.len()) as u8;
+        for (index, byte) in last_block.iter().rev().enumerate() {
+            let inside_padding = ((index as u8) < padding_len) as u8;
+            invalid |= inside_padding & (*byte != padding_len) as u8;
+        }
+
+        match invalid {
+            0 => Ok(last_block.len() - padding_len as usize),
+            _ => Err(Error::WrongPadding),
+        }
+    }
+}
+
+/// Zeros and the padding length as the last byte, as in ANSI X9.23
+///
+/// The padding is always added, unpadding runs in constant time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AnsiX923;
+
+impl Padding for AnsiX923 {
+    fn padding(&mut self, block_len: usize, data_len: usize) -> Vec<u8> {
+        let padding_len = padding_len(block_len, data_len);
+        let mut padding = vec![0x00; padding_len];
+        padding[padding_len - 1] = padding_len as u8;
+        padding
+    }
+
+    fn data_len(&self, last_block: &[u8]) -> Result<usize, Error> {
+        let padding_len = *last_block.last().ok_or(Error::WrongInputSize)?;
+        let mut invalid =
+            (padding_len == 0) as u8 | (padding_len as usize > last_block.len()) as u8;
+        for (index, byte) in last_block.iter().rev().enumerate().skip(1) {
+            let inside_padding = ((index as u8) < padding_len) as u8;
+            invalid |= inside_padding & (*byte != 0x00) as u8;
+        }
+
+        match invalid {
+            0 => Ok(last_block.len() - padding_len as usize),
+            _ => Err(Error::WrongPadding),
+        }
+    }
+}
+
+/// `0x80` followed by zeros, as in ISO/IEC 7816-4
+/// (also known as ISO/IEC 9797-1 padding method 2)
+///
+/// The padding is always added, unpadding runs in constant time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Iso7816;
+
+impl Padding for Iso7816 {
+    fn padding(&mut self, block_len: usize, data_len: usize) -> Vec<u8> {
+        let mut padding = vec![0x00; padding_len(block_len, data_len)];
+        padding[0] = 0x80;
+        padding
+    }
+
+    /// The whole block is scanned for the marker,
+    /// so the time doesn't depend on its position
+    fn data_len(&self, last_block: &[u8]) -> Result<usize, Error> {
+        if last_block.is_empty() {
+            return Err(Error::WrongInputSize);
+        }
+        let (mut found, mut invalid, mut data_len) = (0u8, 0u8, 0usize);
+        for (index, byte) in last_block.iter().enumerate().rev() {
+            let searching = found ^ 1;
+            let is_marker = (*byte == 0x80) as u8;
+            invalid |= searching & (is_marker ^ 1) & (*byte != 0x00) as u8;
+            let hit = searching & is_marker;
+            data_len |= index * hit as usize;
+            found |= hit;
+        }
+
+        match invalid | (found ^ 1) {
+            0 => Ok(data_len),
+            _ => Err(Error::WrongPadding),
+        }
+    }
+}
+
+/// Arbitrary bytes and the padding length as the last byte, as in ISO 10126
+///
+/// The arbitrary bytes come from `fill`, pass your random number generator there.
+/// Only the last byte can be checked, unpadding runs in constant time
+pub struct Iso10126<F: FnMut(&mut [u8])> {
+    fill: F,
+}
+
+impl<F: FnMut(&mut [u8])> Iso10126<F> {
+    pub fn new(fill: F) -> Self {
+        Self { fill }
+    }
+}
+
+impl<F: FnMut(&mut [u8])> Padding for Iso10126<F> {
+    fn padding(&mut self, block_len: usize, data_len: usize) -> Vec<u8> {
+        let padding_len = padding_len(block_len, data_len);
+        let mut padding = vec![0x00; padding_len];
+        (self.fill)(&mut padding[..padding_len - 1]);
+        padding[padding_len - 1] = padding_len as u8;
+        padding
+    }
+
+    fn data_len(&self, last_block: &[u8]) -> Result<usize, Error> {
+        let padding_len = *last_block.last().ok_or(Error::WrongInputSize)? as usize;
+        match padding_len != 0 && padding_len <= last_block.len() {
+            true => Ok(last_block.len() - padding_len),
+            false => Err(Error::WrongPadding),
+        }
+    }
+}
+
+/// Zeros up to the next multiple of the block size, nothing if the data is aligned
+///
+/// Trailing zeros of the data itself can't be told from the padding and are removed too,
+/// so use it only for data that can't end with zero. Unpadding runs in constant time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ZeroPadding;
+
+impl Padding for ZeroPadding {
+    fn padding(&mut self, block_len: usize, data_len: usize) -> Vec<u8> {
+        vec![0x00; (block_len - data_len % block_len) % block_len]
+    }
+
+    fn data_len(&self, last_block: &[u8]) -> Result<usize, Error> {
+        let mut data_len = 0;
+        for (index, byte) in last_block.iter().enumerate() {
+            let nonzero = (*byte != 0x00) as usize;
+            data_len = nonzero * (index + 1) + (nonzero ^ 1) * data_len;
+        }
+        Ok(data_len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(mut padding: impl Padding, data_len: usize, padded: &[u8]) {
+        let data = vec![0xaa; data_len];
+        assert_eq!(padding.pad(&data, 8), padded, "pad {data_len}");
+        assert_eq!(padding.unpad(padded, 8), Ok(&data[..]), "unpad {data_len}");
+    }
+
+    #[test]
+    fn test_pkcs7() {
+        check(Pkcs7, 0, &[0x08; 8]);
+        check(Pkcs7, 5, &hex::decode("aaaaaaaaaa030303").unwrap());
+        check(Pkcs7, 7, &hex::decode("aaaaaaaaaaaaaa01").unwrap());
+        check(
+            Pkcs7,
+            8,
+            &hex::decode("aaaaaaaaaaaaaaaa0808080808080808").unwrap(),
+        );
+
+        assert_eq!(Pkcs7.data_len(&[0x01, 0x02, 0x03, 0x01]), Ok(3));
+        assert_eq!(Pkcs7.data_len(&[0x01, 0x02, 0x02, 0x02]), Ok(2));
+        assert_eq!(Pkcs7.data_len(&[0x04, 0x04, 0x04, 0x04]), Ok(0));
+        assert_eq!(
+            Pkcs7.data_len(&[0x01, 0x02, 0x03, 0x00]),
+            Err(Error::WrongPadding)
+        );
+        assert_eq!(
+            Pkcs7.data_len(&[0x05, 0x05, 0x05, 0x05]),
+            Err(Error::WrongPadding)
+        );
+        assert_eq!(
+            Pkcs7.data_len(&[0x01, 0x02, 0x03, 0x02]),
+            Err(Error::WrongPadding)
+        );
+        assert_eq!(
+            Pkcs7.data_len(&[0x03, 0x02, 0x03, 0x03]),
+            Err(Error::WrongPadding)
+        );
+    }
+
+    #[test]
+    fn test_ansi_x923() {
+        check(AnsiX923, 0, &hex::decode("0000000000000008").unwrap());
+        check(AnsiX923, 5, &hex::decode("aaaaaaaaaa000003").unwrap());
+        check(AnsiX923, 7, &hex::decode("aaaaaaaaaaaaaa01").unwrap());
+
+        assert_eq!(AnsiX923.data_len(&[0x01, 0x00, 0x00, 0x03]), Ok(1));
+        assert_eq!(
+            AnsiX923.data_len(&[0x01, 0x01, 0x00, 0x03]),
+            Err(Error::WrongPadding)
+        );
+        assert_eq!(
+            AnsiX923.data_len(&[0x00, 0x00, 0x00, 0x05]),
+            Err(Error::WrongPadding)
+        );
+        assert_eq!(
+            AnsiX923.data_len(&[0x01, 0x00, 0x00, 0x00]),
+            Err(Error::WrongPadding)
+        );
+    }
+
+    #[test]
+    fn test_iso7816() {
+        check(Iso7816, 0, &hex::decode("8000000000000000").unwrap());
+        check(Iso7816, 5, &hex::decode("aaaaaaaaaa800000").unwrap());
+        check(Iso7816, 7, &hex::decode("aaaaaaaaaaaaaa80").unwrap());
+
+        assert_eq!(Iso7816.data_len(&[0x80, 0x80, 0x00, 0x00]), Ok(1));
+        assert_eq!(
+            Iso7816.data_len(&[0x00, 0x00, 0x00, 0x00]),
+            Err(Error::WrongPadding)
+        );
+        assert_eq!(
+            Iso7816.data_len(&[0x00, 0x80, 0x01, 0x00]),
+            Err(Error::WrongPadding)
+        );
+        assert_eq!(Iso7816.data_len(&[]), Err(Error::WrongInputSize));
+    }
+
+    #[test]
+    fn test_iso10126() {
+        let mut counter = 0;
+        let mut padding = Iso10126::new(|bytes: &mut [u8]| {
+            bytes.iter_mut().for_each(|byte| {
+                counter += 1;
+                *byte = counter;
+            })
+        });
+        assert_eq!(
+            padding.pad(&[0xaa; 5], 8),
+            hex::decode("aaaaaaaaaa010203").unwrap()
+        );
+        assert_eq!(
+            padding.pad(&[0xaa; 5], 8),
+            hex::decode("aaaaaaaaaa030403").unwrap()
+        );
+        assert_eq!(padding.pad(&[], 4), hex::decode("05060704").unwrap());
+
+        assert_eq!(
+            padding.unpad(&hex::decode("aaaaaaaaaa030403").unwrap(), 8),
+            Ok(&[0xaa; 5][..])
+        );
+        assert_eq!(
+            padding.data_len(&[0x01, 0x02, 0x03, 0x00]),
+            Err(Error::WrongPadding)
+        );
+        assert_eq!(
+            padding.data_len(&[0x01, 0x02, 0x03, 0x05]),
+            Err(Error::WrongPadding)
+        );
+    }
+
+    #[test]
+    fn test_zero() {
+        check(ZeroPadding, 0, &[]);
+        check(ZeroPadding, 5, &hex::decode("aaaaaaaaaa000000").unwrap());
+        check(ZeroPadding, 8, &[0xaa; 8]);
+
+        // Trailing zeros of the data are lost
+        assert_eq!(
+            ZeroPadding.unpad(&[0x01, 0x00, 0x02, 0x00], 4),
+            Ok(&[0x01, 0x00, 0x02][..])
+        );
+    }
+
+    #[test]
+    fn test_wrong_input() {
+        assert_eq!(Pkcs7.unpad(&[0x01; 7], 8), Err(Error::WrongInputSize));
+        assert_eq!(Pkcs7.unpad(&[], 8), Err(Error::WrongInputSize));
+        assert_eq!(AnsiX923.unpad(&[], 8), Err(Error::WrongInputSize));
+        assert_eq!(ZeroPadding.unpad(&[], 8), Ok(&[][..]));
+    }
+}
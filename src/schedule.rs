@@ -0,0 +1,195 @@
+use std::fmt;
+
+use crate::{
+    block::{rc5_decode, rc5_encode},
+    cipher::{DecodeBlock, EncodeBlock},
+    key::{Key, MixinKey},
+    settings::Rc5Settings,
+    word::Word,
+    Error,
+};
+
+/// Magic bytes at the start of a serialized [`KeySchedule`]
+const MAGIC: &[u8; 4] = b"RC5S";
+/// Current version of the serialization format
+const VERSION: u8 = 1;
+/// magic + version + word bits + rounds count
+const HEADER_LEN: usize = MAGIC.len() + 3;
+
+/// Keyed RC5 cipher: the expanded key table `S` together with the rounds count
+///
+/// It is the output of the key expansion (check 4.3 in [the specification](https://www.grc.com/r&d/rc5.pdf)),
+/// so once it's built the [`Key`] itself is no longer needed.
+/// The schedule can be exported by [`KeySchedule::to_bytes`] and loaded back
+/// by [`KeySchedule::from_bytes`], e.g. to provision a device that should never see the key.
+///
+/// # Format
+/// ```text
+/// | "RC5S" | version: u8 | w: u8 | r: u8 | S[0] .. S[2r + 1] |
+/// ```
+/// All words of `S` are little-endian, `w` is the word size in bits.
+#[derive(Clone, PartialEq, Eq)]
+pub struct KeySchedule<W: Word> {
+    rounds_count: u8,
+    table: Vec<W>,
+}
+
+impl<W: Word> KeySchedule<W> {
+    pub fn new(key: impl Key, settings: Rc5Settings<W>) -> Self {
+        Self {
+            rounds_count: settings.rounds_count,
+            table: key.mixin::<W>(settings.rounds_count),
+        }
+    }
+
+    pub fn rounds_count(&self) -> u8 {
+        self.rounds_count
+    }
+
+    /// Serialize the schedule, check [`KeySchedule`] for the format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.table.len() * W::BYTES);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(W::BITS);
+        bytes.push(self.rounds_count);
+        for word in self.table.iter() {
+            bytes.append(&mut word.into_le_bytes());
+        }
+        bytes
+    }
+
+    /// Load the schedule serialized by [`KeySchedule::to_bytes`]
+    ///
+    /// `Error` - if the header is malformed, the version is unknown,
+    /// the word size differs from `W` or the table length doesn't match the rounds count
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() < HEADER_LEN || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(Error::MalformedSchedule);
+        }
+        let (version, word_bits, rounds_count) = (bytes[4], bytes[5], bytes[6]);
+        if version != VERSION {
+            return Err(Error::UnsupportedScheduleVersion(version));
+        }
+        if word_bits != W::BITS {
+            return Err(Error::ScheduleWordSizeMismatch {
+                expected: W::BITS,
+                actual: word_bits,
+            });
+        }
+
+        let table = &bytes[HEADER_LEN..];
+        if table.len() != 2 * (rounds_count as usize + 1) * W::BYTES {
+            return Err(Error::MalformedSchedule);
+        }
+
+        Ok(Self {
+            rounds_count,
+            table: table.chunks(W::BYTES).map(W::from_le_bytes).collect(),
+        })
+    }
+}
+
+impl<W: Word> EncodeBlock<W> for KeySchedule<W> {
+    fn encode_block(&self, block: (W, W)) -> (W, W) {
+        rc5_encode(block, &self.table, self.rounds_count)
+    }
+}
+
+impl<W: Word> DecodeBlock<W> for KeySchedule<W> {
+    fn decode_block(&self, block: (W, W)) -> (W, W) {
+        rc5_decode(block, &self.table, self.rounds_count)
+    }
+}
+
+/// The table is as secret as the key itself, so it's not printed
+impl<W: Word> fmt::Debug for KeySchedule<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeySchedule")
+            .field("word_bits", &W::BITS)
+            .field("rounds_count", &self.rounds_count)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DecodeRc5, EncodeRc5};
+
+    const KEY: [u8; 16] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E,
+        0x0F,
+    ];
+
+    #[test]
+    fn test_same_as_key() {
+        let schedule = KeySchedule::new(KEY, Rc5Settings::default());
+        let pt = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77];
+        let ct = [0x2D, 0xDC, 0x14, 0x9B, 0xCF, 0x08, 0x8B, 0x9E];
+
+        assert_eq!(schedule.encode(&pt).unwrap(), ct);
+        assert_eq!(schedule.decode(&ct).unwrap(), pt);
+        assert_eq!(
+            schedule.encode(&pt).unwrap(),
+            pt.encode_rc5(KEY).unwrap().as_slice()
+        );
+        assert_eq!(
+            schedule.decode(&pt).unwrap(),
+            pt.decode_rc5(KEY).unwrap().as_slice()
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let schedule = KeySchedule::new(KEY, Rc5Settings::<u64>::new(20));
+        let bytes = schedule.to_bytes();
+        assert_eq!(bytes.len(), HEADER_LEN + 2 * 21 * 8);
+        assert_eq!(&bytes[..HEADER_LEN], b"RC5S\x01\x40\x14");
+        assert_eq!(KeySchedule::<u64>::from_bytes(&bytes), Ok(schedule));
+    }
+
+    #[test]
+    fn test_wrong_input() {
+        let bytes = KeySchedule::new(KEY, Rc5Settings::<u16>::new(12)).to_bytes();
+
+        assert_eq!(
+            KeySchedule::<u16>::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(Error::MalformedSchedule)
+        );
+        assert_eq!(
+            KeySchedule::<u16>::from_bytes(&bytes[..5]),
+            Err(Error::MalformedSchedule)
+        );
+
+        let mut wrong_magic = bytes.clone();
+        wrong_magic[0] = b'X';
+        assert_eq!(
+            KeySchedule::<u16>::from_bytes(&wrong_magic),
+            Err(Error::MalformedSchedule)
+        );
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[4] = 2;
+        assert_eq!(
+            KeySchedule::<u16>::from_bytes(&wrong_version),
+            Err(Error::UnsupportedScheduleVersion(2))
+        );
+
+        assert_eq!(
+            KeySchedule::<u32>::from_bytes(&bytes),
+            Err(Error::ScheduleWordSizeMismatch {
+                expected: 32,
+                actual: 16
+            })
+        );
+    }
+
+    #[test]
+    fn test_debug_hides_table() {
+        assert_eq!(
+            format!("{:?}", KeySchedule::new(KEY, Rc5Settings::default())),
+            "KeySchedule { word_bits: 32, rounds_count: 12, .. }"
+        );
+    }
+}
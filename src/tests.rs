@@ -44,6 +44,29 @@ fn decode_b() {
     assert_eq!(pt.as_slice(), &ct.decode_rc5(key).unwrap());
 }
 
+#[test]
+fn array_encode_a() {
+    let key = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E,
+        0x0F,
+    ];
+    let pt = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77];
+    let ct: [u8; 8] = pt.encode_rc5_array(key);
+    assert_eq!(ct, [0x2D, 0xDC, 0x14, 0x9B, 0xCF, 0x08, 0x8B, 0x9E]);
+    assert_eq!(ct.decode_rc5_array(key), pt);
+}
+
+#[test]
+fn array_case8_12_4() {
+    let ct = [0x00, 0x01, 0x00, 0x01]
+        .encode_rc5_array_with_settings([0x00, 0x01, 0x02, 0x03], Rc5Settings::<u8>::new(12));
+    assert_eq!(ct, [0x21, 0x2a, 0x21, 0x2a]);
+    assert_eq!(
+        ct.decode_rc5_array_with_settings([0x00, 0x01, 0x02, 0x03], Rc5Settings::<u8>::new(12)),
+        [0x00, 0x01, 0x00, 0x01]
+    );
+}
+
 #[test]
 fn case8_12_4() {
     assert_eq!(
@@ -162,6 +185,136 @@ fn case128_28_32() {
     );
 }
 
+/// Keys shorter than a word or not a multiple of it: the first vectors
+/// of [RFC 2040](https://www.rfc-editor.org/rfc/rfc2040#section-9) with the zero IV
+#[test]
+fn key_not_multiple_of_word() {
+    let settings = Rc5Settings::<u32>::new;
+
+    let ct = [0x7a, 0x7b, 0xba, 0x4d, 0x79, 0x11, 0x1d, 0x1e];
+    assert_eq!(
+        [0x00; 8].encode_rc5_with_settings([0x00], settings(0)),
+        Ok(ct.to_vec())
+    );
+    assert_eq!(
+        ct.decode_rc5_with_settings([0x00], settings(0)),
+        Ok(vec![0x00; 8])
+    );
+
+    let key = [0x01, 0x02, 0x03, 0x04, 0x05];
+    let ct = [0x97, 0xe0, 0x78, 0x78, 0x37, 0xed, 0x31, 0x7f];
+    assert_eq!(
+        [0xff; 8].encode_rc5_with_settings(key, settings(12)),
+        Ok(ct.to_vec())
+    );
+    assert_eq!(
+        ct.decode_rc5_with_settings(key, settings(12)),
+        Ok(vec![0xff; 8])
+    );
+}
+
+#[test]
+fn traced_encode_a() {
+    let key = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E,
+        0x0F,
+    ];
+    let pt = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77];
+    let ct = [0x2D, 0xDC, 0x14, 0x9B, 0xCF, 0x08, 0x8B, 0x9E];
+
+    let mut recorder = TraceRecorder::new();
+    assert_eq!(
+        ct.as_slice(),
+        &pt.encode_rc5_traced(key, Rc5Settings::default(), &mut recorder)
+            .unwrap()
+    );
+    // input, whitening & two half-rounds per round
+    assert_eq!(recorder.records().len(), 2 + 2 * 12);
+    assert_eq!(
+        recorder.records().first().unwrap().event,
+        TraceEvent::Input {
+            a: 0x33221100,
+            b: 0x77665544
+        }
+    );
+    assert_eq!(
+        recorder.records().last().unwrap().event,
+        TraceEvent::HalfRound {
+            round: 12,
+            register: Register::B,
+            a: 0x9B14DC2D,
+            b: 0x9E8B08CF,
+            rotation: 0x9B14DC2D % 32,
+        }
+    );
+}
+
+#[test]
+fn traced_decode_blocks() {
+    let key = [0x00, 0x01, 0x02, 0x03];
+    let ct = [0x21, 0x2a, 0x21, 0x2a];
+
+    let mut recorder = TraceRecorder::new();
+    assert_eq!(
+        &ct.decode_rc5_traced(key, Rc5Settings::<u8>::new(12), &mut recorder)
+            .unwrap(),
+        &[0x00, 0x01, 0x00, 0x01]
+    );
+    assert_eq!(recorder.records().last().unwrap().block, 1);
+    assert_eq!(
+        recorder.records().last().unwrap().event,
+        TraceEvent::Whitening { a: 0x00, b: 0x01 }
+    );
+    assert!(recorder.to_csv().ends_with("1,whitening,,,00,01,\n"));
+}
+
+#[test]
+fn padded_encode_a() {
+    let key = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E,
+        0x0F,
+    ];
+    let pt = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77];
+    let ct = [0x2D, 0xDC, 0x14, 0x9B, 0xCF, 0x08, 0x8B, 0x9E];
+
+    // The padding block follows the unchanged first one
+    let padded = pt.encode_rc5_padded(key, Pkcs7);
+    assert_eq!(&padded[..8], ct.as_slice());
+    assert_eq!(padded[8..], [0x08; 8].encode_rc5(key).unwrap());
+    assert_eq!(padded.decode_rc5_padded(key, Pkcs7).unwrap(), pt);
+}
+
+#[test]
+fn padded_any_length() {
+    let key = [0x00, 0x01, 0x02, 0x03];
+    let settings = || Rc5Settings::<u16>::new(12);
+    for len in 0..10 {
+        let pt = (1..=len).collect::<Vec<u8>>();
+        let ct = pt.encode_rc5_padded_with_settings(key, settings(), Iso7816);
+        assert_eq!(ct.len(), (len as usize / 4 + 1) * 4);
+        assert_eq!(
+            ct.decode_rc5_padded_with_settings(key, settings(), Iso7816),
+            Ok(pt.clone())
+        );
+
+        let ct = pt.encode_rc5_padded_with_settings(key, settings(), ZeroPadding);
+        assert_eq!(ct.len(), (len as usize + 3) / 4 * 4);
+        assert_eq!(
+            ct.decode_rc5_padded_with_settings(key, settings(), ZeroPadding),
+            Ok(pt)
+        );
+    }
+
+    assert_eq!(
+        [0x00; 6].decode_rc5_padded(key, Pkcs7),
+        Err(Error::WrongInputSize)
+    );
+    assert_eq!(
+        [0x00; 8].decode_rc5_padded(key, AnsiX923),
+        Err(Error::WrongPadding)
+    );
+}
+
 #[cfg(feature = "secrecy")]
 #[test]
 fn secrecy_case8_1_4() {
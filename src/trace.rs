@@ -0,0 +1,256 @@
+use std::fmt::Write;
+
+use crate::word::Word;
+
+/// Which of the two block registers was updated by a half-round
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    A,
+    B,
+}
+
+/// A single step of RC5 encryption or decryption
+///
+/// During encryption the observer receives [`TraceEvent::Input`], then
+/// [`TraceEvent::Whitening`] (`A = A + S[0]`, `B = B + S[1]`) and then two
+/// [`TraceEvent::HalfRound`] per round. Decryption goes in the reverse order:
+/// the half-rounds come first (`B` before `A`, from the last round to the
+/// first) and the subtraction of `S[0]` & `S[1]` is reported as whitening at the end.
+///
+/// Check 4.1 & 4.2 in [the specification](https://www.grc.com/r&d/rc5.pdf).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceEvent<W: Word> {
+    /// The block as it was loaded from the input
+    Input { a: W, b: W },
+    /// The block after the key-table words `S[0]` & `S[1]` are applied
+    Whitening { a: W, b: W },
+    /// The block after `register` was updated in round `round`
+    ///
+    /// `rotation` is the amount of bits of the data-dependent rotation,
+    /// already reduced module `w`
+    HalfRound {
+        round: u8,
+        register: Register,
+        a: W,
+        b: W,
+        rotation: u32,
+    },
+}
+
+/// Receives every step of the algorithm for each processed block
+///
+/// Observers are passed by generic parameter, so the [`NoTrace`]
+/// implementation is fully removed by the compiler
+pub trait Observer<W: Word> {
+    fn observe(&mut self, event: TraceEvent<W>);
+}
+
+/// Observer that ignores all events.
+/// Used by the non-traced methods
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoTrace;
+
+impl<W: Word> Observer<W> for NoTrace {
+    #[inline(always)]
+    fn observe(&mut self, _event: TraceEvent<W>) {}
+}
+
+impl<W: Word, F: FnMut(TraceEvent<W>)> Observer<W> for F {
+    fn observe(&mut self, event: TraceEvent<W>) {
+        self(event)
+    }
+}
+
+/// Event together with the index of the block it belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceRecord<W: Word> {
+    pub block: usize,
+    pub event: TraceEvent<W>,
+}
+
+/// Observer that stores all events so that they can be
+/// exported to CSV or JSON and diffed against another implementation
+///
+/// Words are exported as big-endian hex numbers with `w / 4` digits,
+/// as the reference implementation from the specification prints them
+#[derive(Debug, Clone)]
+pub struct TraceRecorder<W: Word> {
+    records: Vec<TraceRecord<W>>,
+}
+
+impl<W: Word> Default for TraceRecorder<W> {
+    fn default() -> Self {
+        Self {
+            records: Vec::new(),
+        }
+    }
+}
+
+impl<W: Word> Observer<W> for TraceRecorder<W> {
+    fn observe(&mut self, event: TraceEvent<W>) {
+        let block = match (self.records.last(), event) {
+            (None, _) => 0,
+            (Some(last), TraceEvent::Input { .. }) => last.block + 1,
+            (Some(last), _) => last.block,
+        };
+        self.records.push(TraceRecord { block, event });
+    }
+}
+
+impl<W: Word> TraceRecorder<W> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn records(&self) -> &[TraceRecord<W>] {
+        &self.records
+    }
+
+    /// One line per event with the header
+    /// `block,step,round,register,a,b,rotation`.
+    /// Fields that are not applicable to the step are left empty
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("block,step,round,register,a,b,rotation\n");
+        for record in self.records.iter() {
+            let (step, round, register, a, b, rotation) = record.columns();
+            writeln!(
+                csv,
+                "{block},{step},{round},{register},{a},{b},{rotation}",
+                block = record.block,
+                round = round.map(|r| r.to_string()).unwrap_or_default(),
+                register = register.unwrap_or_default(),
+                rotation = rotation.map(|r| r.to_string()).unwrap_or_default(),
+            )
+            .expect("Write to `String` can't fail");
+        }
+        csv
+    }
+
+    /// JSON array of objects with the same fields as [`TraceRecorder::to_csv`],
+    /// non-applicable fields are omitted
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("[");
+        for (index, record) in self.records.iter().enumerate() {
+            let (step, round, register, a, b, rotation) = record.columns();
+            if index != 0 {
+                json.push(',');
+            }
+            write!(json, r#"{{"block":{},"step":"{step}""#, record.block)
+                .expect("Write to `String` can't fail");
+            if let Some(round) = round {
+                write!(json, r#","round":{round}"#).expect("Write to `String` can't fail");
+            }
+            if let Some(register) = register {
+                write!(json, r#","register":"{register}""#).expect("Write to `String` can't fail");
+            }
+            write!(json, r#","a":"{a}","b":"{b}""#).expect("Write to `String` can't fail");
+            if let Some(rotation) = rotation {
+                write!(json, r#","rotation":{rotation}"#).expect("Write to `String` can't fail");
+            }
+            json.push('}');
+        }
+        json.push(']');
+        json
+    }
+}
+
+type Columns = (
+    &'static str,
+    Option<u8>,
+    Option<&'static str>,
+    String,
+    String,
+    Option<u32>,
+);
+
+impl<W: Word> TraceRecord<W> {
+    fn columns(&self) -> Columns {
+        match self.event {
+            TraceEvent::Input { a, b } => ("input", None, None, to_hex(a), to_hex(b), None),
+            TraceEvent::Whitening { a, b } => ("whitening", None, None, to_hex(a), to_hex(b), None),
+            TraceEvent::HalfRound {
+                round,
+                register,
+                a,
+                b,
+                rotation,
+            } => (
+                "half_round",
+                Some(round),
+                Some(match register {
+                    Register::A => "A",
+                    Register::B => "B",
+                }),
+                to_hex(a),
+                to_hex(b),
+                Some(rotation),
+            ),
+        }
+    }
+}
+
+fn to_hex<W: Word>(word: W) -> String {
+    format!(
+        "{:0width$x}",
+        word.to_u128()
+            .expect("Safe because `Word` is at most 128 bits"),
+        width = W::BYTES * 2
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recorder_blocks() {
+        let mut recorder = TraceRecorder::<u8>::new();
+        recorder.observe(TraceEvent::Input { a: 0, b: 1 });
+        recorder.observe(TraceEvent::Whitening { a: 2, b: 3 });
+        recorder.observe(TraceEvent::Input { a: 4, b: 5 });
+        assert_eq!(
+            recorder
+                .records()
+                .iter()
+                .map(|r| r.block)
+                .collect::<Vec<_>>(),
+            [0, 0, 1]
+        );
+    }
+
+    #[test]
+    fn test_csv() {
+        let mut recorder = TraceRecorder::<u16>::new();
+        recorder.observe(TraceEvent::Whitening { a: 0xab, b: 0x1 });
+        recorder.observe(TraceEvent::HalfRound {
+            round: 1,
+            register: Register::B,
+            a: 0xffff,
+            b: 0x10,
+            rotation: 15,
+        });
+        assert_eq!(
+            recorder.to_csv(),
+            "block,step,round,register,a,b,rotation\n\
+             0,whitening,,,00ab,0001,\n\
+             0,half_round,1,B,ffff,0010,15\n"
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let mut recorder = TraceRecorder::<u8>::new();
+        recorder.observe(TraceEvent::Input { a: 0x0f, b: 0xf0 });
+        recorder.observe(TraceEvent::HalfRound {
+            round: 2,
+            register: Register::A,
+            a: 0x01,
+            b: 0x02,
+            rotation: 2,
+        });
+        assert_eq!(
+            recorder.to_json(),
+            r#"[{"block":0,"step":"input","a":"0f","b":"f0"},{"block":0,"step":"half_round","round":2,"register":"A","a":"01","b":"02","rotation":2}]"#
+        );
+    }
+}
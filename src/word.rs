@@ -42,6 +42,8 @@ pub trait Word:
     const BITS: u8;
     // Count of bytes inside word
     const BYTES: usize = (Self::BITS / 8) as usize;
+    // Count of bytes inside block (pair of words)
+    const BLOCK_BYTES: usize = 2 * Self::BYTES;
 }
 
 macro_rules! impl_word_size {
@@ -171,16 +173,23 @@ mod presudo_random_key_sequence_test {
     }
 }
 
+pub(crate) trait RotationAmount: Word {
+    /// The real amount of bits that a data-dependent rotation
+    /// by `self` moves: `self` module `<Self as Word>::BITS`
+    fn rotation_amount(self) -> u32 {
+        self.rem(<Self as From<u8>>::from(Self::BITS))
+            .to_u32()
+            .expect("Safe because `rem` of u8 before")
+    }
+}
+impl<W: Word> RotationAmount for W {}
+
 pub(crate) trait RotateWordLeft: Word {
     /// Shifts the bits to the left by a specified amount,
     /// `n` module `<Self as Word>::BITS`, wrapping the truncated
     /// bits to the beginning of the resulting integer.
     fn rotate_word_left(self, n: Self) -> Self {
-        self.rotate_left(
-            n.rem(<Self as From<u8>>::from(Self::BITS))
-                .to_u32()
-                .expect("Safe because `rem` of u8 before"),
-        )
+        self.rotate_left(n.rotation_amount())
     }
 }
 impl<W: Word> RotateWordLeft for W {}
@@ -190,18 +199,21 @@ pub(crate) trait RotateWordRight: Word {
     /// `n` module `<Self as Word>::BITS`, wrapping the truncated
     /// bits to the beginning of the resulting integer.
     fn rotate_word_right(self, n: Self) -> Self {
-        self.rotate_right(
-            n.rem(<Self as From<u8>>::from(Self::BITS))
-                .to_u32()
-                .expect("Safe because `rem` of u8 before"),
-        )
+        self.rotate_right(n.rotation_amount())
     }
 }
 impl<W: Word> RotateWordRight for W {}
 
 #[cfg(test)]
 mod rotate_word {
-    use super::{RotateWordLeft, RotateWordRight};
+    use super::{RotateWordLeft, RotateWordRight, RotationAmount};
+
+    #[test]
+    fn test_rotation_amount() {
+        assert_eq!(7u8.rotation_amount(), 7);
+        assert_eq!(9u8.rotation_amount(), 1);
+        assert_eq!(u64::MAX.rotation_amount(), 63);
+    }
 
     #[test]
     fn test_rotate_left() {